@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use log::Level;
 use serde::{Deserialize, Serialize};
 
@@ -9,16 +11,56 @@ pub struct SchemaVersion {
 pub mod v1 {
     use super::*;
 
+    /// Version of the frames below.
+    ///
+    /// Adding optional keys to [`JsonFrame`] doesn't change the version, since readers of the
+    /// older frames ignore them.
     pub const SCHEMA_VERSION: SchemaVersion = SchemaVersion { schema_version: 1 };
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
     pub struct JsonFrame {
+        /// The message, without the key-value fields
         pub data: String,
+        /// The key-value fields, e.g. `{"peer": "10.0.0.1", "rssi": "-52"}`, formatted like the
+        /// message
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        pub fields: BTreeMap<String, String>,
         /// Unix timestamp in nanoseconds
         pub host_timestamp: i64,
         pub level: Option<Level>,
         pub location: Location,
         pub target_timestamp: String,
+        /// The target timestamp as a number, if the `defmt::timestamp!` is a single integer
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub target_timestamp_value: Option<TargetTimestamp>,
+        /// Estimated Unix timestamp in nanoseconds at which the frame was logged, from the
+        /// target timestamp and the host time at which the frame was received
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub wall_clock_estimate: Option<i64>,
+        /// Number of device resets that were detected before the frame, if requested
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub boot: Option<u32>,
+    }
+
+    /// A target timestamp as a number, e.g. `{"value": 1500, "unit": "us"}`
+    #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+    pub struct TargetTimestamp {
+        pub value: u64,
+        pub unit: TimestampUnit,
+    }
+
+    /// The unit of a [`TargetTimestamp`], as given by the display hint of the timestamp
+    #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum TimestampUnit {
+        /// Microseconds, `"us"`
+        Us,
+        /// Milliseconds, `"ms"`
+        Ms,
+        /// Seconds, `"s"`
+        S,
+        /// Ticks of unknown length, `"ticks"`
+        Ticks,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
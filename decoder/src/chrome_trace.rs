@@ -0,0 +1,262 @@
+//! Exports frames as a trace in the [Chrome Trace Event format], which [Perfetto] and
+//! `chrome://tracing` can display.
+//!
+//! Log frames become instant events on a track per log level or per module, and the enter and
+//! exit frames of a `defmt::span!` become a duration slice on the `spans` track. Frames logged by
+//! different cores, see `defmt::core_id!`, go to separate processes.
+//!
+//! [Chrome Trace Event format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [Perfetto]: https://ui.perfetto.dev
+
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    str::FromStr,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::{Frame, SpanEvent};
+
+/// Which track a log frame is put on
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Tracks {
+    /// A track per log level, e.g. `INFO`
+    #[default]
+    Level,
+    /// A track per module path, e.g. `app::radio`
+    Module,
+}
+
+impl FromStr for Tracks {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "level" => Ok(Tracks::Level),
+            "module" => Ok(Tracks::Module),
+            _ => anyhow::bail!("unknown trace tracks '{}', expected 'level' or 'module'", s),
+        }
+    }
+}
+
+/// Writes frames as a trace in the Chrome Trace Event format.
+///
+/// The trace is a JSON array that is written event by event. [`Self::finish`] closes it; trace
+/// viewers also accept a trace that was cut short, e.g. because the program was interrupted.
+pub struct ChromeTrace<W: Write> {
+    writer: W,
+    tracks: Tracks,
+    /// Thread IDs of the tracks, by core ID and track name
+    track_ids: HashMap<(Option<u8>, String), u64>,
+    /// Cores whose process was named
+    cores: Vec<u8>,
+    is_empty: bool,
+}
+
+impl<W: Write> ChromeTrace<W> {
+    /// Starts a trace, which is written to `writer`.
+    pub fn new(mut writer: W, tracks: Tracks) -> io::Result<Self> {
+        writer.write_all(b"[")?;
+        Ok(Self {
+            writer,
+            tracks,
+            track_ids: HashMap::new(),
+            cores: vec![],
+            is_empty: true,
+        })
+    }
+
+    /// Adds `frame`, logged in the module at `module_path`, to the trace.
+    ///
+    /// Frames are skipped if their timestamp isn't a single integer, see `defmt::timestamp!`.
+    /// Timestamps are converted to microseconds according to their display hint, e.g. `:ms`;
+    /// timestamps without a time display hint are taken as microseconds.
+    pub fn write_frame(&mut self, frame: &Frame<'_>, module_path: Option<&str>) -> io::Result<()> {
+        let Some(timestamp) = frame.timestamp() else {
+            return Ok(());
+        };
+        let ts = match timestamp.to_duration() {
+            Some(duration) => duration.as_nanos() as f64 / 1e3,
+            None => timestamp.value as f64,
+        };
+        let pid = frame.core_id().unwrap_or(0);
+        if let Some(core_id) = frame.core_id() {
+            self.name_process(core_id)?;
+        }
+
+        let mut args = Map::new();
+        for (name, value) in frame.fields() {
+            args.insert(name.to_string(), Value::String(value));
+        }
+        if let Some(module_path) = module_path {
+            args.insert("module".to_string(), Value::String(module_path.to_string()));
+        }
+
+        let event = match frame.span() {
+            Some(span) => {
+                let tid = self.track_id(frame.core_id(), "spans")?;
+                // the name of the span, without the marker that `display_message` adds
+                let name = frame.display_fragments().collect::<String>();
+                let ph = match span {
+                    SpanEvent::Enter => "B",
+                    SpanEvent::Exit => "E",
+                };
+                json!({ "name": name, "ph": ph, "ts": ts, "pid": pid, "tid": tid, "args": args })
+            }
+            None => {
+                let level = frame.level().map(|level| level.as_str().to_uppercase());
+                let track = match self.tracks {
+                    Tracks::Level => level.clone().unwrap_or_else(|| "PRINTLN".to_string()),
+                    Tracks::Module => module_path.unwrap_or("<unknown>").to_string(),
+                };
+                let tid = self.track_id(frame.core_id(), &track)?;
+                json!({
+                    "name": frame.display_message().to_string(),
+                    "cat": level.unwrap_or_else(|| "PRINTLN".to_string()),
+                    "ph": "i",
+                    "s": "t",
+                    "ts": ts,
+                    "pid": pid,
+                    "tid": tid,
+                    "args": args,
+                })
+            }
+        };
+        self.write_event(&event)
+    }
+
+    /// Flushes the events written so far.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Closes the trace and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(b"\n]\n")?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    /// Returns the thread ID of a track, and names the track the first time it's used.
+    fn track_id(&mut self, core_id: Option<u8>, name: &str) -> io::Result<u64> {
+        let key = (core_id, name.to_string());
+        if let Some(tid) = self.track_ids.get(&key) {
+            return Ok(*tid);
+        }
+
+        let tid = self.track_ids.len() as u64 + 1;
+        self.track_ids.insert(key, tid);
+        let pid = core_id.unwrap_or(0);
+        self.write_event(&json!({
+            "name": "thread_name", "ph": "M", "pid": pid, "tid": tid, "args": { "name": name },
+        }))?;
+        Ok(tid)
+    }
+
+    fn name_process(&mut self, core_id: u8) -> io::Result<()> {
+        if self.cores.contains(&core_id) {
+            return Ok(());
+        }
+
+        self.cores.push(core_id);
+        let name = format!("core {core_id}");
+        self.write_event(&json!({
+            "name": "process_name", "ph": "M", "pid": core_id, "args": { "name": name },
+        }))
+    }
+
+    fn write_event(&mut self, event: &Value) -> io::Result<()> {
+        let separator = if self.is_empty { "\n" } else { ",\n" };
+        self.is_empty = false;
+        self.writer.write_all(separator.as_bytes())?;
+        serde_json::to_writer(&mut self.writer, event)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Encoding, Table, TableEntry, Tag};
+
+    #[test]
+    fn spans_and_instant_events() {
+        let table = Table {
+            timestamp: Some(TableEntry::new_without_symbol(
+                Tag::Timestamp,
+                "{=u8:ms}".into(),
+            )),
+            core_id: None,
+            entries: [
+                (
+                    0,
+                    TableEntry::new_without_symbol(Tag::SpanEnter, "boot".into()),
+                ),
+                (
+                    1,
+                    TableEntry::new_without_symbol(Tag::SpanExit, "boot".into()),
+                ),
+                (
+                    2,
+                    TableEntry::new_without_symbol(Tag::Warn, "low{@mv=u8}".into()),
+                ),
+            ]
+            .into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let mut trace = ChromeTrace::new(vec![], Tracks::Level).unwrap();
+        for bytes in [[0, 0, 1].as_slice(), &[2, 0, 2, 90], &[1, 0, 5]] {
+            let frame = table.decode(bytes).unwrap().0;
+            trace.write_frame(&frame, Some("app")).unwrap();
+        }
+        let trace = trace.finish().unwrap();
+
+        let trace: Value = serde_json::from_slice(&trace).unwrap();
+        let expected = json!([
+            { "name": "thread_name", "ph": "M", "pid": 0, "tid": 1, "args": { "name": "spans" } },
+            { "name": "boot", "ph": "B", "ts": 1000.0, "pid": 0, "tid": 1, "args": { "module": "app" } },
+            { "name": "thread_name", "ph": "M", "pid": 0, "tid": 2, "args": { "name": "WARN" } },
+            {
+                "name": "low", "cat": "WARN", "ph": "i", "s": "t", "ts": 2000.0, "pid": 0, "tid": 2,
+                "args": { "mv": "90", "module": "app" },
+            },
+            { "name": "boot", "ph": "E", "ts": 5000.0, "pid": 0, "tid": 1, "args": { "module": "app" } },
+        ]);
+        assert_eq!(trace, expected);
+    }
+
+    #[test]
+    fn module_tracks() {
+        let table = Table {
+            timestamp: Some(TableEntry::new_without_symbol(
+                Tag::Timestamp,
+                "{=u8}".into(),
+            )),
+            core_id: Some(TableEntry::new_without_symbol(Tag::CoreId, "{=u8}".into())),
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let mut trace = ChromeTrace::new(vec![], Tracks::Module).unwrap();
+        let frame = table.decode(&[0, 0, 7, 1]).unwrap().0;
+        trace.write_frame(&frame, None).unwrap();
+        let trace = trace.finish().unwrap();
+
+        let trace: Value = serde_json::from_slice(&trace).unwrap();
+        let expected = json!([
+            { "name": "process_name", "ph": "M", "pid": 1, "args": { "name": "core 1" } },
+            { "name": "thread_name", "ph": "M", "pid": 1, "tid": 1, "args": { "name": "<unknown>" } },
+            {
+                "name": "hello", "cat": "INFO", "ph": "i", "s": "t", "ts": 7.0, "pid": 1, "tid": 1,
+                "args": {},
+            },
+        ]);
+        assert_eq!(trace, expected);
+    }
+}
@@ -0,0 +1,212 @@
+//! Estimates the wall-clock time at which frames were logged.
+//!
+//! The device's timestamps count the time since it booted, and the host only knows when it
+//! received a frame, which is some transport latency after it was logged. [`ClockSync`] fits a
+//! line through recent pairs of device timestamps and host receive times; its slope follows the
+//! drift of the device's clock, and it is placed below all pairs, since no frame is received
+//! before it is logged.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
+
+use crate::Frame;
+
+/// How many of the most recent frames the fit takes into account by default
+const DEFAULT_WINDOW: usize = 256;
+
+/// How far the device's clock may drift from the host's, in parts per million.
+///
+/// Over a short time span, the receive times are dominated by the transport, e.g. a burst of
+/// buffered frames that arrives all at once; this keeps such spans from skewing the fit.
+const MAX_DRIFT_PPM: f64 = 1000.0;
+
+/// Correlates the timestamps of frames with the host time at which they were received.
+#[derive(Debug)]
+pub struct ClockSync {
+    samples: VecDeque<Sample>,
+    window: usize,
+}
+
+#[derive(Debug)]
+struct Sample {
+    /// Device time in seconds
+    device: f64,
+    received: SystemTime,
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl ClockSync {
+    /// Creates a `ClockSync` that fits the `window` most recent frames.
+    pub fn new(window: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(1),
+        }
+    }
+
+    /// Adds `frame`, received at `received`, and sets its estimated [`Frame::wall_clock`].
+    ///
+    /// Frames whose timestamp isn't a single integer with a time display hint, like
+    /// `{=u64:us}`, have no wall-clock time. If the timestamp goes backwards, e.g. because the
+    /// device was reset, the fit starts over.
+    pub fn update(&mut self, frame: &mut Frame<'_>, received: SystemTime) {
+        let Some(device) = frame
+            .timestamp()
+            .and_then(|timestamp| timestamp.to_duration())
+        else {
+            return;
+        };
+        let device = device.as_secs_f64();
+
+        if self.samples.back().is_some_and(|last| device < last.device) {
+            self.samples.clear();
+        }
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample { device, received });
+
+        frame.set_wall_clock(self.estimate(device));
+    }
+
+    /// Returns the estimated wall-clock time of `device` seconds.
+    fn estimate(&self, device: f64) -> Option<SystemTime> {
+        let first = self.samples.front()?;
+        // relative to the first sample, to keep the precision of the `f64`s
+        let points = self
+            .samples
+            .iter()
+            .map(|sample| {
+                let x = sample.device - first.device;
+                let y = seconds_between(first.received, sample.received);
+                (x, y)
+            })
+            .collect::<Vec<_>>();
+
+        let slope = fit_slope(&points)
+            .unwrap_or(1.0)
+            .clamp(1.0 - MAX_DRIFT_PPM / 1e6, 1.0 + MAX_DRIFT_PPM / 1e6);
+        let offset = points
+            .iter()
+            .map(|(x, y)| y - slope * x)
+            .fold(f64::INFINITY, f64::min);
+
+        let seconds = offset + slope * (device - first.device);
+        let duration = Duration::try_from_secs_f64(seconds.abs()).ok()?;
+        if seconds >= 0.0 {
+            first.received.checked_add(duration)
+        } else {
+            first.received.checked_sub(duration)
+        }
+    }
+}
+
+/// Returns the seconds from `start` to `end`, which are negative if `end` is earlier.
+fn seconds_between(start: SystemTime, end: SystemTime) -> f64 {
+    match end.duration_since(start) {
+        Ok(duration) => duration.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+/// Returns the slope of the least-squares line through `points`, if they span any time.
+fn fit_slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Encoding, Table, TableEntry, Tag};
+
+    fn table(timestamp: &str) -> Table {
+        Table {
+            timestamp: Some(TableEntry::new_without_symbol(
+                Tag::Timestamp,
+                timestamp.into(),
+            )),
+            core_id: None,
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        }
+    }
+
+    fn frame_bytes(timestamp: u32) -> Vec<u8> {
+        let mut bytes = vec![0, 0];
+        bytes.extend(timestamp.to_le_bytes());
+        bytes
+    }
+
+    /// Milliseconds from `start` to `time`
+    fn millis_since(start: SystemTime, time: SystemTime) -> f64 {
+        seconds_between(start, time) * 1e3
+    }
+
+    #[test]
+    fn latency_and_drift() {
+        let table = table("{=u32:us}");
+        let boot = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut clock_sync = ClockSync::default();
+
+        // the device's clock runs 100 ppm slow; frames take 2 to 12 ms to arrive
+        let mut estimates = vec![];
+        for i in 0..100u32 {
+            let device_us = i * 100_000;
+            let logged = boot + Duration::from_micros(u64::from(device_us) * 10_001 / 10_000);
+            let latency = Duration::from_millis(2 + u64::from(i * 7 % 11));
+
+            let mut frame = table.decode(&frame_bytes(device_us)).unwrap().0;
+            clock_sync.update(&mut frame, logged + latency);
+            estimates.push((logged, frame.wall_clock().unwrap()));
+        }
+
+        // the first estimate is off by the latency of the first frame
+        let (logged, estimate) = estimates[0];
+        assert_eq!(millis_since(logged, estimate).round(), 2.0);
+        // later estimates are off by about the smallest latency, despite the drift
+        let (logged, estimate) = estimates[99];
+        assert!((millis_since(logged, estimate) - 2.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn reset() {
+        let table = table("{=u32:ms}");
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut clock_sync = ClockSync::default();
+
+        let mut frame = table.decode(&frame_bytes(60_000)).unwrap().0;
+        clock_sync.update(&mut frame, start);
+
+        // the device was reset 10 s later, and logs 5 ms after booting
+        let received = start + Duration::from_secs(10);
+        let mut frame = table.decode(&frame_bytes(5)).unwrap().0;
+        clock_sync.update(&mut frame, received);
+        assert_eq!(frame.wall_clock(), Some(received));
+    }
+
+    #[test]
+    fn ticks() {
+        let table = table("{=u32}");
+        let mut clock_sync = ClockSync::default();
+        let mut frame = table.decode(&frame_bytes(1)).unwrap().0;
+        clock_sync.update(&mut frame, SystemTime::now());
+        assert_eq!(frame.wall_clock(), None);
+    }
+}
@@ -5,7 +5,7 @@ use std::{
 
 use crate::{Arg, DecodeError, FormatSliceElement, Table};
 use byteorder::{ReadBytesExt, LE};
-use defmt_parser::{get_max_bitfield_range, Fragment, Parameter, Type};
+use defmt_parser::{get_max_bitfield_range, Parameter, Type};
 
 pub(crate) struct Decoder<'t, 'b> {
     table: &'t Table,
@@ -95,10 +95,7 @@ impl<'t, 'b> Decoder<'t, 'b> {
         let mut params = defmt_parser::parse(format, defmt_parser::ParserMode::ForwardsCompatible)
             .map_err(|_| DecodeError::Malformed)?
             .iter()
-            .filter_map(|frag| match frag {
-                Fragment::Parameter(param) => Some(param.clone()),
-                Fragment::Literal(_) => None,
-            })
+            .filter_map(|frag| frag.parameter().cloned())
             .collect::<Vec<_>>();
 
         self.prepare_params(&mut params);
@@ -116,7 +116,9 @@ pub fn parse_impl(elf: &[u8], check_version: bool) -> Result<Option<Table>, anyh
     // second pass to demangle symbols
     let mut map = BTreeMap::new();
     let mut bitflags_map = HashMap::new();
+    let mut enums_map = HashMap::new();
     let mut timestamp = None;
+    let mut core_id = None;
     for entry in elf.symbols() {
         let Ok(name) = entry.name() else {
             continue;
@@ -157,26 +159,18 @@ pub fn parse_impl(elf: &[u8], check_version: bool) -> Result<Option<Table>, anyh
                         name.to_string(),
                     ));
                 }
-                symbol::SymbolTag::Defmt(Tag::BitflagsValue) => {
-                    // Bitflags values always occupy 128 bits / 16 bytes.
-                    const BITFLAGS_VALUE_SIZE: u64 = 16;
-
-                    if entry.size() != BITFLAGS_VALUE_SIZE {
-                        bail!(
-                            "bitflags value does not occupy 16 bytes (symbol `{}`)",
-                            name
-                        );
+                symbol::SymbolTag::Defmt(Tag::CoreId) => {
+                    if core_id.is_some() {
+                        bail!("multiple core ID specifications found");
                     }
 
-                    let section = elf.section_by_index(section_index)?;
-                    let addr = entry.address();
-                    let value = match section.data_range(addr, BITFLAGS_VALUE_SIZE)? {
-                        Some(bytes) => u128::from_le_bytes(bytes.try_into().unwrap()),
-                        None => bail!(
-                            "bitflags value at {:#x} outside of defmt section",
-                            entry.address()
-                        ),
-                    };
+                    core_id = Some(TableEntry::new(
+                        StringEntry::new(Tag::CoreId, sym.data().to_string()),
+                        name.to_string(),
+                    ));
+                }
+                symbol::SymbolTag::Defmt(Tag::BitflagsValue) => {
+                    let value = read_value(&elf, &entry, section_index, "bitflags")?;
                     log::debug!("bitflags value `{}` has value {:#x}", sym.data(), value);
 
                     let segments = sym.data().split("::").collect::<Vec<_>>();
@@ -200,6 +194,31 @@ pub fn parse_impl(elf: &[u8], check_version: bool) -> Result<Option<Table>, anyh
                         value,
                     ));
                 }
+                symbol::SymbolTag::Defmt(Tag::EnumValue) => {
+                    let value = read_value(&elf, &entry, section_index, "enum")?;
+                    log::debug!("enum value `{}` has value {:#x}", sym.data(), value);
+
+                    let segments = sym.data().split("::").collect::<Vec<_>>();
+                    let (enum_name, value_idx, value_name) = match &*segments {
+                        [enum_name, value_idx, value_name] => {
+                            (*enum_name, value_idx.parse::<u128>()?, *value_name)
+                        }
+                        _ => bail!("malformed enum value string '{}'", sym.data()),
+                    };
+
+                    let key = BitflagsKey {
+                        ident: enum_name.into(),
+                        package: sym.package().into(),
+                        disambig: sym.disambiguator().into(),
+                        crate_name: sym.crate_name().map(|s| s.into()),
+                    };
+
+                    enums_map.entry(key).or_insert_with(Vec::new).push((
+                        value_name.into(),
+                        value_idx,
+                        value,
+                    ));
+                }
                 symbol::SymbolTag::Defmt(tag) => {
                     map.insert(
                         entry.address() as usize,
@@ -214,11 +233,48 @@ pub fn parse_impl(elf: &[u8], check_version: bool) -> Result<Option<Table>, anyh
         }
     }
 
-    // Sort bitflags values by the value's index in definition order. Since all values get their own
-    // symbol and section, their order in the final binary is unspecified and can't be relied on, so
-    // we put them back in the original order here.
-    let bitflags = bitflags_map
-        .into_iter()
+    let bitflags = sort_values(bitflags_map);
+
+    // Enum values are looked up by name only, since that's all a `:enum(Name)` hint refers to. If
+    // several tables have the same name, the first one in `BitflagsKey` order is used, so that the
+    // choice doesn't change from run to run.
+    let mut enum_tables = sort_values(enums_map).into_iter().collect::<Vec<_>>();
+    enum_tables.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    let mut enums = HashMap::new();
+    let mut used: Option<BitflagsKey> = None;
+    for (k, values) in enum_tables {
+        if let Some(used) = used.as_ref().filter(|used| used.ident == k.ident) {
+            log::warn!(
+                "multiple `defmt::enum_names!` tables are named `{}`; using the one in package `{}`, not the one in package `{}`",
+                k.ident,
+                used.package,
+                k.package
+            );
+            continue;
+        }
+        enums.insert(k.ident.clone(), values);
+        used = Some(k);
+    }
+
+    Ok(Some(Table {
+        entries: map,
+        timestamp,
+        core_id,
+        bitflags,
+        enums,
+        encoding,
+    }))
+}
+
+/// Sorts the values of each `defmt::bitflags!` or `defmt::enum_names!` invocation by the value's
+/// index in definition order.
+///
+/// Since all values get their own symbol and section, their order in the final binary is
+/// unspecified and can't be relied on, so we put them back in the original order here.
+fn sort_values(
+    map: HashMap<BitflagsKey, Vec<(String, u128, u128)>>,
+) -> HashMap<BitflagsKey, Vec<(String, u128)>> {
+    map.into_iter()
         .map(|(k, mut values)| {
             values.sort_by_key(|(_, index, _)| *index);
             let values = values
@@ -228,14 +284,36 @@ pub fn parse_impl(elf: &[u8], check_version: bool) -> Result<Option<Table>, anyh
 
             (k, values)
         })
-        .collect();
+        .collect()
+}
 
-    Ok(Some(Table {
-        entries: map,
-        timestamp,
-        bitflags,
-        encoding,
-    }))
+/// Reads the value of a `static` generated by `defmt::bitflags!` or `defmt::enum_names!`.
+fn read_value<'data>(
+    elf: &object::File<'data>,
+    entry: &object::Symbol<'data, '_>,
+    section_index: object::SectionIndex,
+    kind: &str,
+) -> anyhow::Result<u128> {
+    // Values always occupy 128 bits / 16 bytes.
+    const VALUE_SIZE: u64 = 16;
+
+    if entry.size() != VALUE_SIZE {
+        bail!(
+            "{} value does not occupy 16 bytes (symbol `{}`)",
+            kind,
+            entry.name().unwrap_or("<unknown>")
+        );
+    }
+
+    let section = elf.section_by_index(section_index)?;
+    match section.data_range(entry.address(), VALUE_SIZE)? {
+        Some(bytes) => Ok(u128::from_le_bytes(bytes.try_into().unwrap())),
+        None => bail!(
+            "{} value at {:#x} outside of defmt section",
+            kind,
+            entry.address()
+        ),
+    }
 }
 
 /// Checks if the version encoded in the symbol table is compatible with this version of the `decoder` crate
@@ -535,6 +613,16 @@ mod tests {
         )
     }
 
+    fn enum_value_symbol(data: &str) -> String {
+        enum_value_symbol_in(data, "pkg")
+    }
+
+    fn enum_value_symbol_in(data: &str, package: &str) -> String {
+        format!(
+            r#"{{"package":"{package}","tag":"defmt_enum_value","data":"{data}","disambiguator":"a","crate_name":"crate"}}"#
+        )
+    }
+
     #[test]
     fn unmerged_sections_use_symbol_address_indices() {
         let elf = unmerged_elf([(0x20, log_symbol("hello", "a"))]);
@@ -561,4 +649,51 @@ mod tests {
 
         assert_eq!(values, &[("A".to_string(), value)]);
     }
+
+    #[test]
+    fn enum_values_are_sorted_by_index() {
+        let elf = unmerged_elf_with_data([
+            (
+                0,
+                enum_value_symbol("UsbStatus::1::Stall"),
+                1u128.to_le_bytes().to_vec(),
+            ),
+            (
+                0,
+                enum_value_symbol("UsbStatus::0::Ok"),
+                0u128.to_le_bytes().to_vec(),
+            ),
+        ]);
+        let table = parse_impl(&elf, true).unwrap().unwrap();
+
+        assert_eq!(
+            table.enums["UsbStatus"],
+            [("Ok".to_string(), 0), ("Stall".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn enum_tables_with_the_same_name_are_chosen_deterministically() {
+        let tables = [
+            (
+                0,
+                enum_value_symbol_in("UsbStatus::0::Ok", "b"),
+                0u128.to_le_bytes().to_vec(),
+            ),
+            (
+                0,
+                enum_value_symbol_in("UsbStatus::0::Idle", "a"),
+                0u128.to_le_bytes().to_vec(),
+            ),
+        ];
+
+        for elf in [
+            unmerged_elf_with_data(tables.clone()),
+            unmerged_elf_with_data(tables.into_iter().rev()),
+        ] {
+            let table = parse_impl(&elf, true).unwrap().unwrap();
+
+            assert_eq!(table.enums["UsbStatus"], [("Idle".to_string(), 0)]);
+        }
+    }
 }
@@ -17,6 +17,8 @@ pub(super) struct Symbol {
     /// * `defmt_fmt`, `defmt_str` for interned format strings and string literals.
     /// * `defmt_trace`, `defmt_debug`, `defmt_info`, `defmt_warn`, `defmt_error` for logging
     ///   messages used at the different log levels.
+    /// * `defmt_span_enter`, `defmt_span_exit` for the frames logged when a `defmt::span!` is
+    ///   entered and exited.
     /// * Anything starting with `defmt_` is reserved for use by defmt, other prefixes are free for
     ///   use by third-party apps (but they all should use a prefix!).
     tag: String,
@@ -49,9 +51,13 @@ impl Symbol {
             "defmt_bitflags" => SymbolTag::Defmt(Tag::Bitflags),
             "defmt_write" => SymbolTag::Defmt(Tag::Write),
             "defmt_timestamp" => SymbolTag::Defmt(Tag::Timestamp),
+            "defmt_core_id" => SymbolTag::Defmt(Tag::CoreId),
             "defmt_bitflags_value" => SymbolTag::Defmt(Tag::BitflagsValue),
+            "defmt_enum_value" => SymbolTag::Defmt(Tag::EnumValue),
             "defmt_str" => SymbolTag::Defmt(Tag::Str),
             "defmt_println" => SymbolTag::Defmt(Tag::Println),
+            "defmt_span_enter" => SymbolTag::Defmt(Tag::SpanEnter),
+            "defmt_span_exit" => SymbolTag::Defmt(Tag::SpanExit),
             "defmt_trace" => SymbolTag::Defmt(Tag::Trace),
             "defmt_debug" => SymbolTag::Defmt(Tag::Debug),
             "defmt_info" => SymbolTag::Defmt(Tag::Info),
@@ -2,11 +2,16 @@ use std::{
     convert::TryFrom,
     fmt::{self, Write as _},
     mem,
+    net::Ipv4Addr,
+    time::{Duration, SystemTime},
 };
 
-use crate::{Arg, BitflagsKey, Table};
+use crate::{Arg, BitflagsKey, Table, Tag};
 use colored::Colorize;
-use defmt_parser::{DisplayHint, Fragment, Level, ParserMode, TimePrecision, Type};
+use defmt_parser::{
+    DisplayHint, Fragment, Level, Parameter, ParserMode, Scale, TimePrecision, Type,
+};
+use serde::{Deserialize, Serialize};
 use time::{macros::format_description, OffsetDateTime};
 
 /// Used to convert a `i128` value into right target type in hex
@@ -46,18 +51,123 @@ pub struct Frame<'t> {
     index: u64,
     timestamp_format: Option<&'t str>,
     timestamp_args: Vec<Arg<'t>>,
+    /// What the timestamp is, if it is a single integer; parsed once from `timestamp_format`
+    timestamp_kind: Option<TimestampKind>,
+    core_id: Option<u8>,
     // Format string
     format: &'t str,
     args: Vec<Arg<'t>>,
+    /// Number of spans this frame is nested in, set by the stream decoders
+    span_depth: usize,
+    /// Ticks of the timestamp between the enter frame and this exit frame of a span
+    span_elapsed: Option<u64>,
+    /// Estimated wall-clock time at which the frame was logged, set by a `ClockSync`
+    wall_clock: Option<SystemTime>,
+    /// Added to the timestamp to extend it past its wraparounds, set by the stream decoders
+    timestamp_epoch: u64,
+    /// Number of device resets detected before this frame, set by the stream decoders
+    boot: u32,
+    /// Whether this is the first frame after a detected device reset
+    follows_reset: bool,
+}
+
+/// The timestamp of a frame as a number, see [`Frame::timestamp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timestamp {
+    pub value: u64,
+    pub unit: TimestampUnit,
+}
+
+impl Timestamp {
+    /// Returns the timestamp as a duration since the start of the device's clock, or `None` if
+    /// the unit is [`TimestampUnit::Ticks`].
+    pub fn to_duration(&self) -> Option<Duration> {
+        self.unit.duration(self.value)
+    }
+}
+
+/// The unit of a [`Timestamp`], as given by the display hint of the `defmt::timestamp!` format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampUnit {
+    /// `:us`, `:tus`
+    Micros,
+    /// `:ms`, `:tms`, `:iso8601ms`
+    Millis,
+    /// `:ts`, `:iso8601s`
+    Seconds,
+    /// No time display hint, so the length of a tick is unknown
+    Ticks,
+}
+
+impl TimestampUnit {
+    /// Returns the duration of `value` units, or `None` for [`TimestampUnit::Ticks`].
+    pub fn duration(self, value: u64) -> Option<Duration> {
+        match self {
+            TimestampUnit::Micros => Some(Duration::from_micros(value)),
+            TimestampUnit::Millis => Some(Duration::from_millis(value)),
+            TimestampUnit::Seconds => Some(Duration::from_secs(value)),
+            TimestampUnit::Ticks => None,
+        }
+    }
+}
+
+/// The unit of a timestamp that is a single integer, and after how many bits it wraps around, if
+/// it is unsigned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TimestampKind {
+    unit: TimestampUnit,
+    bits: Option<u32>,
+}
+
+impl TimestampKind {
+    /// Parses the `defmt::timestamp!` format; returns `None` unless it is a single parameter.
+    fn parse(timestamp_format: &str) -> Option<Self> {
+        let fragments = defmt_parser::parse(timestamp_format, ParserMode::ForwardsCompatible);
+        let fragments = fragments.ok()?;
+        let [Fragment::Parameter(param)] = fragments.as_slice() else {
+            return None;
+        };
+        let bits = match param.ty {
+            Type::U8 => Some(8),
+            Type::U16 => Some(16),
+            // `usize` is 32 bits wide on the targets defmt supports
+            Type::U32 | Type::Usize => Some(32),
+            Type::U64 => Some(64),
+            _ => None,
+        };
+        let unit = match &param.hint {
+            Some(
+                DisplayHint::Seconds(precision)
+                | DisplayHint::Time(precision)
+                | DisplayHint::ISO8601(precision),
+            ) => match precision {
+                TimePrecision::Micros => TimestampUnit::Micros,
+                TimePrecision::Millis => TimestampUnit::Millis,
+                TimePrecision::Seconds => TimestampUnit::Seconds,
+            },
+            _ => TimestampUnit::Ticks,
+        };
+
+        Some(Self { unit, bits })
+    }
+}
+
+/// Marks the frames logged when a `defmt::span!` is entered and exited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpanEvent {
+    Enter,
+    Exit,
 }
 
 impl<'t> Frame<'t> {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         table: &'t Table,
         level: Option<Level>,
         index: u64,
         timestamp_format: Option<&'t str>,
         timestamp_args: Vec<Arg<'t>>,
+        core_id: Option<u8>,
         format: &'t str,
         args: Vec<Arg<'t>>,
     ) -> Self {
@@ -67,8 +177,16 @@ impl<'t> Frame<'t> {
             index,
             timestamp_format,
             timestamp_args,
+            timestamp_kind: timestamp_format.and_then(TimestampKind::parse),
+            core_id,
             format,
             args,
+            span_depth: 0,
+            span_elapsed: None,
+            wall_clock: None,
+            timestamp_epoch: 0,
+            boot: 0,
+            follows_reset: false,
         }
     }
 
@@ -137,6 +255,35 @@ impl<'t> Frame<'t> {
         defmt_parser::parse(self.format, ParserMode::ForwardsCompatible).unwrap()
     }
 
+    /// Returns the key-value fields of this log frame, like `peer` and `rssi` in:
+    ///
+    /// ```ignore
+    /// defmt::info!("connected", peer = addr, rssi = rssi);
+    /// ```
+    ///
+    /// The fields are not part of [`Self::display_message`]; their values are formatted like
+    /// message parameters. [`Self::display_fragments`] yields an empty string for them.
+    pub fn fields(&'t self) -> Vec<(&'t str, String)> {
+        self.fragments()
+            .into_iter()
+            .filter_map(|fragment| match fragment {
+                Fragment::Field(field) => {
+                    let mut value = String::new();
+                    self.format_parameter(&field.parameter, &mut value, &self.args, None)
+                        .ok()?;
+                    Some((field.name, value))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns a struct that will format the key-value fields of this log frame as `key=value`
+    /// pairs, separated by spaces.
+    pub fn display_fields(&'t self) -> DisplayFields<'t> {
+        DisplayFields { frame: self }
+    }
+
     pub fn level(&self) -> Option<Level> {
         self.level
     }
@@ -145,6 +292,103 @@ impl<'t> Frame<'t> {
         self.index
     }
 
+    /// Returns the ID of the core that logged this frame, if the firmware uses `defmt::core_id!`.
+    pub fn core_id(&self) -> Option<u8> {
+        self.core_id
+    }
+
+    /// Returns whether this frame was logged when a `defmt::span!` was entered or exited.
+    pub fn span(&self) -> Option<SpanEvent> {
+        match self.table.entries.get(&(self.index as usize))?.string.tag {
+            Tag::SpanEnter => Some(SpanEvent::Enter),
+            Tag::SpanExit => Some(SpanEvent::Exit),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of spans this frame is nested in.
+    ///
+    /// The enter and exit frames of a span are not nested in the span itself. Only frames returned
+    /// by a [`StreamDecoder`](crate::StreamDecoder) are tracked; otherwise this is 0.
+    pub fn span_depth(&self) -> usize {
+        self.span_depth
+    }
+
+    /// Returns how long the span of this exit frame lasted, in the unit of the [`Self::timestamp`],
+    /// e.g. microseconds for a `defmt::timestamp!("{=u64:us}", ...)`.
+    ///
+    /// This is `None` for other frames, if the enter frame is missing, or if the timestamp isn't
+    /// a single integer. Only frames returned by a [`StreamDecoder`](crate::StreamDecoder) are
+    /// tracked.
+    pub fn span_elapsed(&self) -> Option<u64> {
+        self.span_elapsed
+    }
+
+    pub(crate) fn set_span(&mut self, depth: usize, elapsed: Option<u64>) {
+        self.span_depth = depth;
+        self.span_elapsed = elapsed;
+    }
+
+    /// Returns the estimated wall-clock time at which the frame was logged.
+    ///
+    /// This is only known for frames that were passed to a
+    /// [`ClockSync`](crate::clock_sync::ClockSync).
+    pub fn wall_clock(&self) -> Option<SystemTime> {
+        self.wall_clock
+    }
+
+    pub(crate) fn set_wall_clock(&mut self, wall_clock: Option<SystemTime>) {
+        self.wall_clock = wall_clock;
+    }
+
+    /// Returns the number of device resets that were detected before this frame.
+    ///
+    /// Only frames returned by a [`StreamDecoder`](crate::StreamDecoder) are tracked; it detects
+    /// a reset when the timestamp goes backwards without a plausible wraparound. The resets of
+    /// each core are counted separately.
+    pub fn boot(&self) -> u32 {
+        self.boot
+    }
+
+    /// Returns whether this is the first frame after a detected device reset, see [`Self::boot`].
+    pub fn follows_reset(&self) -> bool {
+        self.follows_reset
+    }
+
+    pub(crate) fn set_timestamp_epoch(&mut self, epoch: u64, boot: u32, follows_reset: bool) {
+        self.timestamp_epoch = epoch;
+        self.boot = boot;
+        self.follows_reset = follows_reset;
+    }
+
+    /// Returns the timestamp as a number, if the `defmt::timestamp!` format is a single integer
+    /// parameter, like `{=u64:us}`.
+    ///
+    /// The unit is inferred from the display hint of the parameter; without a time display hint
+    /// it is [`TimestampUnit::Ticks`]. For frames returned by a
+    /// [`StreamDecoder`](crate::StreamDecoder), timestamps of up to 32 bits are extended to
+    /// 64 bits when they wrap around; [`Self::display_timestamp`] shows the original value.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        let (timestamp, _) = self.device_timestamp()?;
+        Some(Timestamp {
+            value: timestamp.value.checked_add(self.timestamp_epoch)?,
+            ..timestamp
+        })
+    }
+
+    /// Returns the timestamp as sent by the device, and after how many bits it wraps around, if it
+    /// is unsigned.
+    pub(crate) fn device_timestamp(&self) -> Option<(Timestamp, Option<u32>)> {
+        let TimestampKind { unit, bits } = self.timestamp_kind?;
+        let value = match self.timestamp_args.as_slice() {
+            [Arg::Uxx(value)] => u64::try_from(*value).ok()?,
+            [Arg::Ixx(value)] => u64::try_from(*value).ok()?,
+            _ => return None,
+        };
+
+        Some((Timestamp { value, unit }, bits))
+    }
+
     fn format_args(&self, format: &str, args: &[Arg], parent_hint: Option<&DisplayHint>) -> String {
         let params = defmt_parser::parse(format, ParserMode::ForwardsCompatible).unwrap();
         let mut buf = String::new();
@@ -166,97 +410,107 @@ impl<'t> Frame<'t> {
             Fragment::Literal(lit) => {
                 buf.push_str(&lit);
             }
-            Fragment::Parameter(param) => {
-                let hint = param.hint.as_ref().or(parent_hint);
-
-                match &args[param.index] {
-                    Arg::Bool(x) => write!(buf, "{x}")?,
-                    Arg::F32(x) => write!(buf, "{}", ryu::Buffer::new().format(*x))?,
-                    Arg::F64(x) => write!(buf, "{}", ryu::Buffer::new().format(*x))?,
-                    Arg::Uxx(x) => {
-                        match param.ty {
-                            Type::BitField(range) => {
-                                let left_zeroes = mem::size_of::<u128>() * 8 - range.end as usize;
-                                let right_zeroes = left_zeroes + range.start as usize;
-                                // isolate the desired bitfields
-                                let bitfields = (*x << left_zeroes) >> right_zeroes;
-
-                                if let Some(DisplayHint::Ascii) = hint {
-                                    let bstr = bitfields
-                                        .to_be_bytes()
-                                        .iter()
-                                        .skip(right_zeroes / 8)
-                                        .copied()
-                                        .collect::<Vec<u8>>();
-                                    self.format_bytes(&bstr, hint, buf)?
-                                } else {
-                                    self.format_u128(bitfields, hint, buf)?;
-                                }
-                            }
-                            _ => match hint {
-                                Some(DisplayHint::ISO8601(precision)) => {
-                                    self.format_iso8601(*x as u64, precision, buf)?
-                                }
-                                Some(DisplayHint::Debug) => {
-                                    self.format_u128(*x, parent_hint, buf)?
-                                }
-                                _ => self.format_u128(*x, hint, buf)?,
-                            },
+            Fragment::Parameter(param) => self.format_parameter(&param, buf, args, parent_hint)?,
+            // fields are not part of the message; see `Frame::fields`
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn format_parameter(
+        &self,
+        param: &Parameter,
+        buf: &mut String,
+        args: &[Arg],
+        parent_hint: Option<&DisplayHint>,
+    ) -> Result<(), fmt::Error> {
+        let hint = param.hint.as_ref().or(parent_hint);
+
+        match &args[param.index] {
+            Arg::Bool(x) => write!(buf, "{x}")?,
+            Arg::F32(x) => self.format_float(*x, hint, buf)?,
+            Arg::F64(x) => self.format_float(*x, hint, buf)?,
+            Arg::Uxx(x) => {
+                match &param.ty {
+                    Type::BitField(range) => {
+                        let left_zeroes = mem::size_of::<u128>() * 8 - range.end as usize;
+                        let right_zeroes = left_zeroes + range.start as usize;
+                        // isolate the desired bitfields
+                        let bitfields = (*x << left_zeroes) >> right_zeroes;
+
+                        if let Some(DisplayHint::Ascii) = hint {
+                            let bstr = bitfields
+                                .to_be_bytes()
+                                .iter()
+                                .skip(right_zeroes / 8)
+                                .copied()
+                                .collect::<Vec<u8>>();
+                            self.format_bytes(&bstr, hint, buf)?
+                        } else {
+                            self.format_u128(bitfields, hint, buf)?;
                         }
                     }
-                    Arg::Ixx(x) => self.format_i128(*x, param.ty, hint, buf)?,
-                    Arg::Str(x) | Arg::Preformatted(x) => self.format_str(x, hint, buf)?,
-                    Arg::IStr(x) => self.format_str(x, hint, buf)?,
-                    Arg::Format { format, args } => match parent_hint {
-                        Some(DisplayHint::Ascii) => {
-                            buf.push_str(&self.format_args(format, args, parent_hint));
+                    _ => match hint {
+                        Some(DisplayHint::ISO8601(precision)) => {
+                            self.format_iso8601(*x as u64, precision, buf)?
                         }
-                        _ => buf.push_str(&self.format_args(format, args, hint)),
+                        Some(DisplayHint::Debug) => self.format_u128(*x, parent_hint, buf)?,
+                        _ => self.format_u128(*x, hint, buf)?,
                     },
-                    Arg::FormatSequence { args } => {
-                        for arg in args {
-                            buf.push_str(&self.format_args("{=?}", std::slice::from_ref(arg), hint))
-                        }
-                    }
-                    Arg::FormatSlice { elements } => {
-                        match hint {
-                            // Filter Ascii Hints, which contains u8 byte slices
-                            Some(DisplayHint::Ascii)
-                                if elements.iter().filter(|e| e.format == "{=u8}").count() != 0 =>
-                            {
-                                let vals = elements
-                                    .iter()
-                                    .map(|e| match e.args.as_slice() {
-                                        [Arg::Uxx(v)] => {
-                                            u8::try_from(*v).expect("the value must be in u8 range")
-                                        }
-                                        _ => panic!("FormatSlice should only contain one argument"),
-                                    })
-                                    .collect::<Vec<u8>>();
-                                self.format_bytes(&vals, hint, buf)?
-                            }
-                            _ => {
-                                buf.write_str("[")?;
-                                let mut is_first = true;
-                                for element in elements {
-                                    if !is_first {
-                                        buf.write_str(", ")?;
-                                    }
-                                    is_first = false;
-                                    buf.write_str(&self.format_args(
-                                        element.format,
-                                        &element.args,
-                                        hint,
-                                    ))?;
+                }
+            }
+            Arg::Ixx(x) => self.format_i128(*x, param.ty.clone(), hint, buf)?,
+            Arg::Str(x) | Arg::Preformatted(x) => self.format_str(x, hint, buf)?,
+            Arg::IStr(x) => self.format_str(x, hint, buf)?,
+            Arg::Format { format, args } => match parent_hint {
+                Some(DisplayHint::Ascii) => {
+                    buf.push_str(&self.format_args(format, args, parent_hint));
+                }
+                _ => buf.push_str(&self.format_args(format, args, hint)),
+            },
+            Arg::FormatSequence { args } => {
+                for arg in args {
+                    buf.push_str(&self.format_args("{=?}", std::slice::from_ref(arg), hint))
+                }
+            }
+            Arg::FormatSlice { elements } => {
+                match hint {
+                    // Filter byte hints, which apply to u8 byte slices
+                    Some(
+                        DisplayHint::Ascii
+                        | DisplayHint::Mac
+                        | DisplayHint::Uuid
+                        | DisplayHint::Ipv4
+                        | DisplayHint::Hexdump { .. },
+                    ) if elements.iter().filter(|e| e.format == "{=u8}").count() != 0 => {
+                        let vals = elements
+                            .iter()
+                            .map(|e| match e.args.as_slice() {
+                                [Arg::Uxx(v)] => {
+                                    u8::try_from(*v).expect("the value must be in u8 range")
                                 }
-                                buf.write_str("]")?;
+                                _ => panic!("FormatSlice should only contain one argument"),
+                            })
+                            .collect::<Vec<u8>>();
+                        self.format_bytes(&vals, hint, buf)?
+                    }
+                    _ => {
+                        buf.write_str("[")?;
+                        let mut is_first = true;
+                        for element in elements {
+                            if !is_first {
+                                buf.write_str(", ")?;
                             }
+                            is_first = false;
+                            buf.write_str(&self.format_args(element.format, &element.args, hint))?;
                         }
+                        buf.write_str("]")?;
                     }
-                    Arg::Slice(x) => self.format_bytes(x, hint, buf)?,
-                    Arg::Char(c) => write!(buf, "{c}")?,
                 }
             }
+            Arg::Slice(x) => self.format_bytes(x, hint, buf)?,
+            Arg::Char(c) => write!(buf, "{c}")?,
         }
 
         Ok(())
@@ -352,11 +606,38 @@ impl<'t> Frame<'t> {
                     }
                 }
             }
+            Some(DisplayHint::Quantity { scale, unit }) => match i128::try_from(x) {
+                Ok(x) => self.format_quantity(x, scale.as_ref(), unit.as_deref(), buf)?,
+                // only `u128` values above `i128::MAX` end up here; show them unscaled
+                Err(_) => match unit {
+                    Some(unit) => write!(buf, "{x} {unit}")?,
+                    None => write!(buf, "{x}")?,
+                },
+            },
+            Some(DisplayHint::Ipv4) => match u32::try_from(x) {
+                Ok(x) => write!(buf, "{}", Ipv4Addr::from(x))?,
+                Err(_) => write!(buf, "{x}")?,
+            },
+            Some(DisplayHint::Enum { name }) => match self.enum_value_name(name, x) {
+                Some(value_name) => buf.push_str(value_name),
+                None => write!(buf, "{x}")?,
+            },
             _ => write!(buf, "{x}")?,
         }
         Ok(())
     }
 
+    /// Looks up the name of `value` in the `defmt::enum_names!` table called `name`.
+    ///
+    /// Signed values are stored, and looked up, sign-extended to 128 bits.
+    fn enum_value_name(&self, name: &str, value: u128) -> Option<&str> {
+        let values = self.table.enums.get(name)?;
+        values
+            .iter()
+            .find(|(_, v)| *v == value)
+            .map(|(value_name, _)| value_name.as_str())
+    }
+
     fn format_i128(
         &self,
         x: i128,
@@ -393,11 +674,88 @@ impl<'t> Frame<'t> {
                     (true, true) => write!(buf, "{value:#0zero_pad$X}")?,
                 }
             }
+            Some(DisplayHint::Quantity { scale, unit }) => {
+                self.format_quantity(x, scale.as_ref(), unit.as_deref(), buf)?
+            }
+            Some(DisplayHint::Enum { name }) => match self.enum_value_name(name, x as u128) {
+                Some(value_name) => buf.push_str(value_name),
+                None => write!(buf, "{x}")?,
+            },
             _ => write!(buf, "{x}")?,
         }
         Ok(())
     }
 
+    fn format_quantity(
+        &self,
+        x: i128,
+        scale: Option<&Scale>,
+        unit: Option<&str>,
+        buf: &mut String,
+    ) -> Result<(), fmt::Error> {
+        match scale {
+            None => write!(buf, "{x}")?,
+            Some(Scale::FixedPoint(frac_bits)) => {
+                let value = x as f64 / 2f64.powi((*frac_bits).into());
+                // enough fractional digits to tell apart two neighbouring values
+                let precision = (f64::from(*frac_bits) * std::f64::consts::LOG10_2).ceil() as usize;
+                write!(buf, "{value:.precision$}")?;
+            }
+            Some(Scale::Divide(divisor)) => match decimal_digits(*divisor) {
+                Some(digits) => format_decimal(x, -(digits as i32), buf)?,
+                None => {
+                    let value = x as f64 / *divisor as f64;
+                    write!(buf, "{}", ryu::Buffer::new().format(value))?;
+                }
+            },
+            Some(Scale::Decimal { mantissa, exponent }) => match x.checked_mul(*mantissa) {
+                Some(value) => format_decimal(value, *exponent, buf)?,
+                None => {
+                    let value = x as f64 * *mantissa as f64 * 10f64.powi(*exponent);
+                    write!(buf, "{}", ryu::Buffer::new().format(value))?;
+                }
+            },
+        }
+        if let Some(unit) = unit {
+            write!(buf, " {unit}")?;
+        }
+        Ok(())
+    }
+
+    fn format_float<F>(
+        &self,
+        x: F,
+        hint: Option<&DisplayHint>,
+        buf: &mut String,
+    ) -> Result<(), fmt::Error>
+    where
+        F: ryu::Float + fmt::Display + fmt::LowerExp + fmt::UpperExp,
+    {
+        match hint {
+            Some(DisplayHint::Float {
+                precision: Some(precision),
+                scientific,
+                uppercase,
+                zero_pad,
+            }) => match (scientific, uppercase) {
+                (false, _) => write!(buf, "{x:0zero_pad$.precision$}")?,
+                (true, false) => write!(buf, "{x:0zero_pad$.precision$e}")?,
+                (true, true) => write!(buf, "{x:0zero_pad$.precision$E}")?,
+            },
+            Some(DisplayHint::Float {
+                precision: None,
+                scientific: true,
+                uppercase,
+                zero_pad,
+            }) => match uppercase {
+                false => write!(buf, "{x:0zero_pad$e}")?,
+                true => write!(buf, "{x:0zero_pad$E}")?,
+            },
+            _ => write!(buf, "{}", ryu::Buffer::new().format(x))?,
+        }
+        Ok(())
+    }
+
     fn format_bytes(
         &self,
         bytes: &[u8],
@@ -446,6 +804,32 @@ impl<'t> Frame<'t> {
                 }
                 buf.push(']');
             }
+            Some(DisplayHint::Mac) if !bytes.is_empty() => {
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i != 0 {
+                        buf.push(':');
+                    }
+                    write!(buf, "{byte:02x}")?;
+                }
+            }
+            Some(DisplayHint::Uuid) if bytes.len() == 16 => {
+                for (i, byte) in bytes.iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        buf.push('-');
+                    }
+                    write!(buf, "{byte:02x}")?;
+                }
+            }
+            Some(DisplayHint::Ipv4) if bytes.len() == 4 => {
+                write!(buf, "{}.{}.{}.{}", bytes[0], bytes[1], bytes[2], bytes[3])?
+            }
+            Some(DisplayHint::Hexdump { bytes_per_line }) if !bytes.is_empty() => {
+                // the dump always starts on its own line, so that the columns line up
+                if !buf.is_empty() && !buf.ends_with('\n') {
+                    buf.push('\n');
+                }
+                format_hexdump(bytes, *bytes_per_line, buf)?;
+            }
             Some(DisplayHint::Cbor) => {
                 use core::fmt::Write;
                 let parsed = cbor_edn::Sequence::from_cbor(bytes);
@@ -550,6 +934,70 @@ impl<'t> Frame<'t> {
     }
 }
 
+/// Writes `bytes` as lines of offset, hex and ASCII columns, like `hexdump -C`.
+///
+/// Lines are separated, but not terminated, by a newline.
+fn format_hexdump(bytes: &[u8], bytes_per_line: usize, buf: &mut String) -> fmt::Result {
+    for (i, line) in bytes.chunks(bytes_per_line).enumerate() {
+        if i != 0 {
+            buf.push('\n');
+        }
+        write!(buf, "{:08x} ", i * bytes_per_line)?;
+        for column in 0..bytes_per_line {
+            // an extra space separates each group of 8 bytes
+            if column % 8 == 0 {
+                buf.push(' ');
+            }
+            match line.get(column) {
+                Some(byte) => write!(buf, "{byte:02x} ")?,
+                None => buf.push_str("   "),
+            }
+        }
+        buf.push('|');
+        for byte in line {
+            buf.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            });
+        }
+        buf.push('|');
+    }
+    Ok(())
+}
+
+/// Returns `n` if `x` is `10^n`.
+fn decimal_digits(x: u128) -> Option<u32> {
+    let digits = x.ilog10();
+    (10u128.pow(digits) == x).then_some(digits)
+}
+
+/// Writes `value * 10^exponent` without losing precision.
+fn format_decimal(value: i128, exponent: i32, buf: &mut String) -> Result<(), fmt::Error> {
+    if exponent >= 0 {
+        match 10i128
+            .checked_pow(exponent as u32)
+            .and_then(|factor| value.checked_mul(factor))
+        {
+            Some(value) => write!(buf, "{value}"),
+            None => write!(buf, "{value}e{exponent}"),
+        }
+    } else {
+        let digits = exponent.unsigned_abs() as usize;
+        let sign = if value < 0 { "-" } else { "" };
+        let value = value.unsigned_abs();
+        match 10u128.checked_pow(digits as u32) {
+            Some(divisor) => write!(
+                buf,
+                "{sign}{}.{:0digits$}",
+                value / divisor,
+                value % divisor
+            ),
+            None => write!(buf, "{sign}{value}e{exponent}"),
+        }
+    }
+}
+
 pub struct DisplayTimestamp<'t> {
     frame: &'t Frame<'t>,
 }
@@ -574,10 +1022,52 @@ impl fmt::Display for DisplayMessage<'_> {
         let args = self
             .frame
             .format_args(self.frame.format, &self.frame.args, None);
-        f.write_str(&args)
+        match self.frame.span() {
+            Some(SpanEvent::Enter) => write!(f, "-> {args}"),
+            Some(SpanEvent::Exit) => match self.frame.span_elapsed {
+                Some(elapsed) => {
+                    let unit = self
+                        .frame
+                        .timestamp()
+                        .map_or(TimestampUnit::Ticks, |ts| ts.unit);
+                    write!(f, "<- {args} ({})", format_duration(elapsed, unit))
+                }
+                None => write!(f, "<- {args}"),
+            },
+            None => f.write_str(&args),
+        }
+    }
+}
+
+/// Formats `value` units of a timestamp as a duration.
+fn format_duration(value: u64, unit: TimestampUnit) -> String {
+    match unit {
+        TimestampUnit::Micros => format!("{}.{:03}ms", value / 1_000, value % 1_000),
+        TimestampUnit::Millis => format!("{value}ms"),
+        TimestampUnit::Seconds => format!("{value}s"),
+        TimestampUnit::Ticks => format!("{value} ticks"),
+    }
+}
+
+pub struct DisplayFields<'t> {
+    frame: &'t Frame<'t>,
+}
+
+impl fmt::Display for DisplayFields<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&join_fields(&self.frame.fields()))
     }
 }
 
+/// Formats key-value fields as `key=value` pairs, separated by spaces.
+pub(crate) fn join_fields<K: AsRef<str>>(fields: &[(K, String)]) -> String {
+    fields
+        .iter()
+        .map(|(key, value)| format!("{}={value}", key.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// An iterator over the fragments of a log message, formatted as strings.
 ///
 /// See [`Frame::display_fragments`].
@@ -642,10 +1132,16 @@ impl fmt::Display for DisplayFrame<'_> {
             })
             .unwrap_or_default();
 
-        let args = self
-            .frame
-            .format_args(self.frame.format, &self.frame.args, None);
+        let indent = "  ".repeat(self.frame.span_depth);
+        let message = self.frame.display_message();
+
+        let fields = self.frame.fields();
+        let fields = if fields.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", join_fields(&fields))
+        };
 
-        write!(f, "{timestamp}{level}{args}")
+        write!(f, "{timestamp}{level}{indent}{message}{fields}")
     }
 }
@@ -10,11 +10,15 @@ pub const DEFMT_VERSIONS: &[&str] = &["3", "4"];
 #[deprecated = "Please use DEFMT_VERSIONS instead"]
 pub const DEFMT_VERSION: &str = DEFMT_VERSIONS[1];
 
+pub mod chrome_trace;
+pub mod clock_sync;
 mod decoder;
 mod elf2table;
 mod frame;
 pub mod log;
+mod span;
 mod stream;
+mod timestamp;
 
 use std::{
     collections::{BTreeMap, HashMap},
@@ -31,12 +35,13 @@ use crate::{decoder::Decoder, elf2table::parse_impl};
 
 pub use crate::{
     elf2table::{Location, Locations},
-    frame::Frame,
+    frame::{Frame, SpanEvent, Timestamp, TimestampUnit},
     stream::StreamDecoder,
 };
 
 /// Specifies the origin of a format string
 #[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Tag {
     /// Defmt-controlled format string for primitive types.
     Prim,
@@ -50,11 +55,19 @@ pub enum Tag {
     Str,
     /// Defines the global timestamp format.
     Timestamp,
+    /// Defines the format of the core ID in the frame header, see `defmt::core_id!`.
+    CoreId,
 
     /// `static` containing a possible value of a bitflags type.
     BitflagsValue,
+    /// `static` containing a named value of a `defmt::enum_names!` table.
+    EnumValue,
     /// Format string created by `defmt::println!`.
     Println,
+    /// Format string of the frame logged when a `defmt::span!` is entered.
+    SpanEnter,
+    /// Format string of the frame logged when a `defmt::span!` is exited.
+    SpanExit,
 
     Trace,
     Debug,
@@ -68,7 +81,7 @@ impl Tag {
         match self {
             Tag::Trace => Some(Level::Trace),
             Tag::Debug => Some(Level::Debug),
-            Tag::Info => Some(Level::Info),
+            Tag::Info | Tag::SpanEnter | Tag::SpanExit => Some(Level::Info),
             Tag::Warn => Some(Level::Warn),
             Tag::Error => Some(Level::Error),
             _ => None,
@@ -111,7 +124,7 @@ impl StringEntry {
 }
 
 /// Data that uniquely identifies a `defmt::bitflags!` invocation.
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 struct BitflagsKey {
     /// Name of the bitflags struct (this is really redundant with `disambig`).
     ident: String,
@@ -156,8 +169,13 @@ impl Encoding {
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Table {
     timestamp: Option<TableEntry>,
+    #[serde(default)]
+    core_id: Option<TableEntry>,
     entries: BTreeMap<usize, TableEntry>,
     bitflags: HashMap<BitflagsKey, Vec<(String, u128)>>,
+    /// Named values of `defmt::enum_names!` tables, by table name.
+    #[serde(default)]
+    enums: HashMap<String, Vec<(String, u128)>>,
     encoding: Encoding,
 }
 
@@ -243,6 +261,14 @@ impl Table {
             timestamp_args = decoder.decode_format(format)?;
         }
 
+        let core_id = match self.core_id.as_ref() {
+            Some(entry) => match decoder.decode_format(&entry.string.string)?.as_slice() {
+                [Arg::Uxx(id)] => Some(*id as u8),
+                _ => return Err(DecodeError::Malformed),
+            },
+            None => None,
+        };
+
         let (level, format) = self
             .get_with_level(index as usize)
             .map_err(|_| DecodeError::Malformed)?;
@@ -255,6 +281,7 @@ impl Table {
             index,
             timestamp_format,
             timestamp_args,
+            core_id,
             format,
             args,
         );
@@ -277,6 +304,11 @@ impl Table {
     pub fn has_timestamp(&self) -> bool {
         self.timestamp.is_some()
     }
+
+    /// Returns `true` if the frames carry the ID of the core that logged them.
+    pub fn has_core_id(&self) -> bool {
+        self.core_id.is_some()
+    }
 }
 
 // NOTE follows `parser::Type`
@@ -354,13 +386,17 @@ impl Error for DecodeError {}
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     fn test_table(entries: impl IntoIterator<Item = TableEntry>) -> Table {
         Table {
             timestamp: None,
+            core_id: None,
             entries: entries.into_iter().enumerate().collect(),
             bitflags: Default::default(),
+            enums: Default::default(),
             encoding: Encoding::Raw,
         }
     }
@@ -370,12 +406,14 @@ mod tests {
         timestamp: &str,
     ) -> Table {
         Table {
+            core_id: None,
             timestamp: Some(TableEntry::new_without_symbol(
                 Tag::Timestamp,
                 timestamp.into(),
             )),
             entries: entries.into_iter().enumerate().collect(),
             bitflags: Default::default(),
+            enums: Default::default(),
             encoding: Encoding::Raw,
         }
     }
@@ -394,11 +432,13 @@ mod tests {
 
         let table = Table {
             entries,
+            core_id: None,
             timestamp: Some(TableEntry::new_without_symbol(
                 Tag::Timestamp,
                 "{=u8:us}".to_owned(),
             )),
             bitflags: Default::default(),
+            enums: Default::default(),
             encoding: Encoding::Raw,
         };
 
@@ -427,6 +467,7 @@ mod tests {
                     0,
                     None,
                     vec![],
+                    None,
                     "Hello, world!",
                     vec![],
                 ),
@@ -448,6 +489,7 @@ mod tests {
                     1,
                     None,
                     vec![],
+                    None,
                     "The answer is {=u8}!",
                     vec![Arg::Uxx(42)],
                 ),
@@ -492,6 +534,7 @@ mod tests {
                     0,
                     None,
                     vec![],
+                    None,
                     FMT,
                     vec![
                         Arg::Uxx(42),              // u8
@@ -536,6 +579,7 @@ mod tests {
                     0,
                     None,
                     vec![],
+                    None,
                     "The answer is {0=u8} {0=u8}!",
                     vec![Arg::Uxx(42)],
                 ),
@@ -558,6 +602,7 @@ mod tests {
                     1,
                     None,
                     vec![],
+                    None,
                     "The answer is {1=u16} {0=u8} {1=u16}!",
                     vec![Arg::Uxx(42), Arg::Uxx(0xffff)],
                 ),
@@ -590,6 +635,7 @@ mod tests {
                     0,
                     None,
                     vec![],
+                    None,
                     "x={=?}",
                     vec![Arg::Format {
                         format: "Foo {{ x: {=u8} }}",
@@ -631,6 +677,7 @@ mod tests {
                     0,
                     None,
                     vec![],
+                    None,
                     "{=__internal_FormatSequence}",
                     vec![Arg::FormatSequence {
                         args: vec![
@@ -696,6 +743,133 @@ mod tests {
         assert_eq!(frame.display_message().to_string(), "x=Foo { x: 42 }");
     }
 
+    #[test]
+    fn fields() {
+        // defmt::info!("connected {=u8}", 1, peer = Foo { x: 42 }, rssi = -52i8);
+        let entries = vec![
+            TableEntry::new_without_symbol(Tag::Info, "connected {=u8}{@peer}{@rssi}".to_owned()),
+            TableEntry::new_without_symbol(Tag::Derived, "Foo {{ x: {=u8} }}".to_owned()),
+            TableEntry::new_without_symbol(Tag::Prim, "{=i8}".to_owned()),
+        ];
+
+        let table = test_table(entries);
+
+        let bytes = [
+            0, 0, // index
+            1, // the message parameter
+            1, 0,  // index of the struct
+            42, // Foo.x
+            2, 0,    // index of the i8
+            0xcc, // -52
+        ];
+
+        let frame = table.decode(&bytes).unwrap().0;
+        assert_eq!(frame.display_message().to_string(), "connected 1");
+        assert_eq!(
+            frame.fields(),
+            [
+                ("peer", "Foo { x: 42 }".to_string()),
+                ("rssi", "-52".to_string())
+            ]
+        );
+        assert_eq!(
+            frame.display(false).to_string(),
+            "INFO connected 1 peer=Foo { x: 42 } rssi=-52"
+        );
+    }
+
+    #[test]
+    fn spans() {
+        // let _flash = defmt::span!("flash_erase", sector = 3u32);
+        // let _page = defmt::span!("page");
+        // defmt::info!("erasing");
+        let entries = vec![
+            TableEntry::new_without_symbol(Tag::SpanEnter, "flash_erase{@sector=u32}".to_owned()),
+            TableEntry::new_without_symbol(Tag::SpanExit, "flash_erase".to_owned()),
+            TableEntry::new_without_symbol(Tag::SpanEnter, "page".to_owned()),
+            TableEntry::new_without_symbol(Tag::SpanExit, "page".to_owned()),
+            TableEntry::new_without_symbol(Tag::Info, "erasing".to_owned()),
+        ];
+
+        let table = test_table_with_timestamp(entries, "{=u8:us}");
+        let mut stream_decoder = table.new_stream_decoder();
+        stream_decoder.received(&[
+            0, 0, 10, 3, 0, 0, 0, // enter flash_erase at 10 us, sector 3
+            2, 0, 20, // enter page at 20 us
+            4, 0, 30, // erasing
+            3, 0, 40, // exit page
+            1, 0, 250, // exit flash_erase
+        ]);
+
+        let mut lines = vec![];
+        while let Ok(frame) = stream_decoder.decode() {
+            lines.push(frame.display(false).to_string());
+        }
+        assert_eq!(
+            lines,
+            [
+                "0.000010 INFO -> flash_erase sector=3",
+                "0.000020 INFO   -> page",
+                "0.000030 INFO     erasing",
+                "0.000040 INFO   <- page (0.020ms)",
+                "0.000250 INFO <- flash_erase (0.240ms)",
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamp_wraparound_and_reset() {
+        let entries = vec![
+            TableEntry::new_without_symbol(Tag::SpanEnter, "boot".to_owned()),
+            TableEntry::new_without_symbol(Tag::Info, "hello".to_owned()),
+        ];
+
+        let table = test_table_with_timestamp(entries, "{=u8:ms}");
+        let mut stream_decoder = table.new_stream_decoder();
+        stream_decoder.received(&[
+            0, 0, 200, // enter boot
+            1, 0, 250, // hello
+            1, 0, 5, // hello, after a wraparound
+            1, 0, 3, // hello, after a reset
+        ]);
+
+        let mut frames = vec![];
+        while let Ok(frame) = stream_decoder.decode() {
+            frames.push((
+                frame.timestamp().unwrap().value,
+                frame.boot(),
+                frame.follows_reset(),
+                frame.span_depth(),
+            ));
+        }
+        assert_eq!(
+            frames,
+            [
+                (200, 0, false, 0),
+                (250, 0, false, 1),
+                (261, 0, false, 1),
+                (3, 1, true, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn span_exit_without_enter() {
+        let entries = vec![
+            TableEntry::new_without_symbol(Tag::SpanExit, "flash_erase".to_owned()),
+            TableEntry::new_without_symbol(Tag::SpanExit, "page".to_owned()),
+        ];
+
+        let table = test_table_with_timestamp(entries, "{=u8}");
+        let mut stream_decoder = table.new_stream_decoder();
+        stream_decoder.received(&[1, 0, 5, 0, 0, 7]);
+
+        let frame = stream_decoder.decode().unwrap();
+        assert_eq!(frame.span(), Some(SpanEvent::Exit));
+        assert_eq!((frame.span_depth(), frame.span_elapsed()), (0, None));
+        assert_eq!(frame.display_message().to_string(), "<- page");
+    }
+
     #[test]
     fn display_fragments() {
         let entries = vec![
@@ -865,6 +1039,182 @@ mod tests {
         );
     }
 
+    #[test]
+    fn timestamp_value() {
+        let timestamp = |format: &str, bytes: &[u8]| {
+            let entries = [TableEntry::new_without_symbol(Tag::Info, "x".to_owned())];
+            let table = test_table_with_timestamp(entries, format);
+            let bytes = [&[0, 0][..], bytes].concat();
+            table.decode(&bytes).unwrap().0.timestamp()
+        };
+        let value = |value, unit| Some(Timestamp { value, unit });
+
+        assert_eq!(
+            timestamp("{=u32:us}", &[0x40, 0x42, 0x0f, 0]),
+            value(1_000_000, TimestampUnit::Micros)
+        );
+        assert_eq!(
+            timestamp("{=u8:tms}", &[5]),
+            value(5, TimestampUnit::Millis)
+        );
+        assert_eq!(
+            timestamp("{=u8:iso8601s}", &[5]),
+            value(5, TimestampUnit::Seconds)
+        );
+        assert_eq!(timestamp("{=u8}", &[5]), value(5, TimestampUnit::Ticks));
+        assert_eq!(timestamp("{=i8:ms}", &[0xff]), None);
+        assert_eq!(timestamp("{=u8}.{=u8}", &[1, 2]), None);
+
+        let timestamp = value(1_500, TimestampUnit::Micros).unwrap();
+        assert_eq!(timestamp.to_duration(), Some(Duration::from_micros(1_500)));
+        assert_eq!(TimestampUnit::Ticks.duration(1_500), None);
+    }
+
+    #[test]
+    fn display_float_precision_hint() {
+        let mut bytes = vec![
+            0, 0, // index
+            2, // timestamp
+        ];
+        bytes.extend(21.456_f32.to_le_bytes());
+        bytes.extend((-0.1_f64).to_le_bytes());
+
+        decode_and_expect(
+            "{=f32:.2} {=f64:07.3}",
+            &bytes,
+            "0.000002 INFO 21.46 -00.100",
+        );
+    }
+
+    #[test]
+    fn display_float_scientific_hint() {
+        let mut bytes = vec![
+            0, 0, // index
+            2, // timestamp
+        ];
+        bytes.extend(0.00125_f32.to_le_bytes());
+        bytes.extend(1234.5_f64.to_le_bytes());
+        bytes.extend(1234.5_f64.to_le_bytes());
+
+        decode_and_expect(
+            "{=f32:e} {=f64:.2e} {=f64:E}",
+            &bytes,
+            "0.000002 INFO 1.25e-3 1.23e3 1.2345E3",
+        );
+    }
+
+    #[test]
+    fn display_quantity_hints() {
+        let bytes = [
+            0, 0, // index
+            2, // timestamp
+            0x00, 0x40, // i16 0x4000
+            0x1a, 0xf8, // i16 -2022
+            0xd2, 0x04, 0x00, 0x00, // u32 1234
+            0xd2, 0x04, // u16 1234
+            0x0a, // u8 10
+        ];
+
+        decode_and_expect(
+            "{=i16:q15} {=i16:/100 degC} {=u32:scale(0.001)V} {=u16:mV} {=u8:/3}",
+            &bytes,
+            "0.000002 INFO 0.50000 -20.22 degC 1.234 V 1234 mV 3.3333333333333335",
+        );
+    }
+
+    #[test]
+    fn display_network_hints() {
+        let bytes = [
+            0, 0, // index
+            2, // timestamp
+            0x02, 0x00, 0x5e, 0x10, 0xab, 0xcd, // [u8; 6]
+            0x12, 0x3e, 0x45, 0x67, 0xe8, 0x9b, 0x12, 0xd3, // [u8; 16]
+            0xa4, 0x56, 0x42, 0x66, 0x14, 0x17, 0x40, 0x00, //
+            0x01, 0x02, 0xa8, 0xc0, // u32 0xc0a80201
+        ];
+
+        decode_and_expect(
+            "{=[u8; 6]:mac} {=[u8; 16]:uuid} {=u32:ipv4}",
+            &bytes,
+            "0.000002 INFO 02:00:5e:10:ab:cd 123e4567-e89b-12d3-a456-426614174000 192.168.2.1",
+        );
+    }
+
+    #[test]
+    fn display_network_hints_wrong_length() {
+        let bytes = [
+            0, 0, // index
+            2, // timestamp
+            3, 0, 0, 0, // length of the slice
+            10, 0, 1, // slice content
+        ];
+
+        decode_and_expect("{=[u8]:ipv4}", &bytes, "0.000002 INFO [10, 0, 1]");
+    }
+
+    #[test]
+    fn display_enum_hint() {
+        let mut table = test_table([TableEntry::new_without_symbol(
+            Tag::Info,
+            "{=u8:enum(UsbStatus)} {=u8:enum(UsbStatus)} {=i16:enum(Error)} {=u8:enum(Missing)}"
+                .to_owned(),
+        )]);
+        table.enums.insert(
+            "UsbStatus".into(),
+            vec![("Ok".into(), 0), ("Stall".into(), 1), ("Nak".into(), 2)],
+        );
+        table
+            .enums
+            .insert("Error".into(), vec![("Timeout".into(), -110i128 as u128)]);
+
+        let bytes = [
+            0, 0, // index
+            1, // u8 1
+            7, // u8 7
+            0x92, 0xff, // i16 -110
+            1,    // u8 1
+        ];
+
+        let frame = table.decode(&bytes).unwrap().0;
+        assert_eq!(frame.display_message().to_string(), "Stall 7 Timeout 1");
+    }
+
+    #[test]
+    fn display_hexdump_hint() {
+        let bytes = [
+            0, 0, // index
+            2, // timestamp
+            20, 0, 0, 0, // length of the slice
+            b'H', b'e', b'l', b'l', b'o', b',', b' ', b'w', //
+            b'o', b'r', b'l', b'd', b'!', 0x00, 0x01, 0x7f, //
+            0xff, 0x12, 0x34, 0x56, // slice content
+        ];
+
+        decode_and_expect(
+            "page {=[u8]:hexdump}",
+            &bytes,
+            "0.000002 INFO page \n\
+             00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 00 01 7f |Hello, world!...|\n\
+             00000010  ff 12 34 56                                      |..4V|",
+        );
+    }
+
+    #[test]
+    fn display_hexdump_hint_bytes_per_line() {
+        let bytes = [
+            0, 0, // index
+            2, // timestamp
+            b'd', b'e', b'f', b'm', b't', // [u8; 5]
+        ];
+
+        decode_and_expect(
+            "{=[u8; 5]:hexdump4}",
+            &bytes,
+            "0.000002 INFO 00000000  64 65 66 6d |defm|\n\
+             00000004  74          |t|",
+        );
+    }
+
     #[test]
     fn bools_simple() {
         let bytes = [
@@ -1151,11 +1501,13 @@ mod tests {
 
         let table = Table {
             entries,
+            core_id: None,
             timestamp: Some(TableEntry::new_without_symbol(
                 Tag::Timestamp,
                 "{=u8:us}".to_owned(),
             )),
             bitflags: Default::default(),
+            enums: Default::default(),
             encoding: Encoding::Raw,
         };
 
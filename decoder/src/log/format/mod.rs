@@ -1,13 +1,36 @@
 use super::{DefmtRecord, Payload};
-use crate::Frame;
+use crate::{frame::join_fields, Frame, Timestamp, TimestampUnit};
 use colored::{Color, ColoredString, Colorize, Styles};
 use dissimilar::Chunk;
 use log::{Level, Record as LogRecord};
 use regex::Regex;
-use std::{fmt::Write, path::Path};
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    path::Path,
+    str::FromStr,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant, SystemTime},
+};
+use time::{
+    format_description::{BorrowedFormatItem, OwnedFormatItem},
+    macros::format_description,
+    OffsetDateTime,
+};
 
 mod parser;
 
+/// The format of the `{T}` specifier if none is given.
+const DEFAULT_HOST_TIME_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[hour]:[minute]:[second].[subsecond digits:3]");
+
+/// The format of the `{W}` specifier if none is given.
+const DEFAULT_WALL_CLOCK_FORMAT: &[BorrowedFormatItem<'_>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6]Z");
+
+/// The color of the timestamps of frames that follow a gap, see [`FormatterConfig::highlight_gaps`].
+const GAP_COLOR: Color = Color::Magenta;
+
 /// Representation of what a [LogSegment] can be.
 #[derive(Debug, PartialEq, Clone)]
 #[non_exhaustive]
@@ -72,8 +95,66 @@ pub(super) enum LogMetadata {
     ///
     /// Prints the timestamp at which something was logged.
     /// For a log printed with a timestamp 123456 ms, this prints "123456".
+    /// [`FormatterConfig::timestamps`] can make it print a relative or delta timestamp instead.
     Timestamp,
 
+    /// `{tr}` format specifier.
+    ///
+    /// Prints the time elapsed between the first frame of the same core and this one, according
+    /// to the timestamps.
+    /// For a log printed 1.5 seconds after the first one with a `{=u64:us}` timestamp, this
+    /// prints "1.500000".
+    RelativeTimestamp,
+
+    /// `{td}` format specifier.
+    ///
+    /// Prints the time elapsed between the previous frame of the same core and this one, according
+    /// to the timestamps. For a log printed 20 ms after the previous one with a `{=u64:us}`
+    /// timestamp, this prints "+0.020000".
+    DeltaTimestamp,
+
+    /// `{E}` format specifier.
+    ///
+    /// Prints the host time elapsed since the formatter printed its first defmt frame, in seconds.
+    /// For a log printed 1.5 seconds after the first frame, this prints "1.500".
+    HostElapsed,
+
+    /// `{T}` format specifier.
+    ///
+    /// Prints the host's UTC wall-clock time at which something was printed, as formatted by the
+    /// optional [format description] in parentheses
+    /// (default: `[hour]:[minute]:[second].[subsecond digits:3]`).
+    /// For a log printed at 13:37:00 UTC, `{T([hour]:[minute])}` prints "13:37".
+    ///
+    /// [format description]: https://time-rs.github.io/book/api/format-description.html
+    HostTimestamp(Option<OwnedFormatItem>),
+
+    /// `{W}` format specifier.
+    ///
+    /// Prints the estimated UTC wall-clock time at which something was logged, as formatted by
+    /// the optional format description in parentheses, like for `{T}`
+    /// (default: `[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:6]Z`).
+    /// The estimate correlates the timestamps with the host time at which frames were received,
+    /// see [`ClockSync`](crate::clock_sync::ClockSync).
+    /// For a log logged at 13:37:00 UTC, `{W([hour]:[minute])}` prints "13:37".
+    WallClock(Option<OwnedFormatItem>),
+
+    /// `{i}` format specifier.
+    ///
+    /// Prints the index of the interned format string of the log frame.
+    FrameIndex,
+
+    /// `{source}` format specifier.
+    ///
+    /// Prints the name of the input the log was read from, e.g. "stdin".
+    SourceName,
+
+    /// `{core}` format specifier.
+    ///
+    /// Prints the ID of the core that logged something, as set up with `defmt::core_id!`.
+    /// For a log from core 1, this prints "1".
+    CoreId,
+
     /// Represents formats specified within nested curly brackets in the formatting string.
     NestedLogSegments(Vec<LogSegment>),
 }
@@ -199,6 +280,12 @@ impl Formatter {
         }
     }
 
+    /// Sets the name of the input that is being decoded, which the `{source}` specifier prints.
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.formatter.source_name = Some(name.into());
+        self
+    }
+
     /// Format a defmt frame using this formatter.
     pub fn format_frame<'a>(
         &self,
@@ -209,29 +296,36 @@ impl Formatter {
     ) -> String {
         let (timestamp, level) = super::timestamp_and_level_from_frame(&frame);
 
-        // HACK: use match instead of let, because otherwise compilation fails
-        #[allow(clippy::match_single_binding)]
-        match format_args!("{}", frame.display_message()) {
-            args => {
-                let log_record = &LogRecord::builder()
-                    .args(args)
-                    .module_path(module_path)
-                    .file(file)
-                    .line(line)
-                    .build();
-
-                let record = DefmtRecord {
-                    log_record,
-                    payload: Payload { level, timestamp },
-                };
+        let payload = Payload {
+            level,
+            timestamp,
+            timestamp_value: frame.timestamp(),
+            core_id: frame.core_id(),
+            index: Some(frame.index()),
+            fields: super::owned_fields(&frame),
+            span_depth: frame.span_depth(),
+            wall_clock: frame.wall_clock(),
+            boot: frame.boot(),
+            follows_reset: frame.follows_reset(),
+        };
 
-                self.format(&record)
-            }
-        }
+        with_defmt_record(
+            format_args!("{}", frame.display_message()),
+            module_path,
+            file,
+            line,
+            payload,
+            |record| self.format(record),
+        )
     }
 
     /// Format the given [`DefmtRecord`] (which is an internal type).
     pub(super) fn format(&self, record: &DefmtRecord) -> String {
+        self.formatter.timestamps.lock().unwrap().advance(
+            record.core_id(),
+            record.timestamp_value(),
+            record.follows_reset(),
+        );
         self.formatter.format(&Record::Defmt(record))
     }
 }
@@ -249,6 +343,12 @@ impl HostFormatter {
         }
     }
 
+    /// Sets the name of the input that is being decoded, which the `{source}` specifier prints.
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.formatter.source_name = Some(name.into());
+        self
+    }
+
     /// Format the given [`log::Record`].
     pub fn format(&self, record: &LogRecord) -> String {
         self.formatter.format(&Record::Host(record))
@@ -258,6 +358,119 @@ impl HostFormatter {
 #[derive(Debug)]
 struct InternalFormatter {
     format: Vec<LogSegment>,
+    /// Whether `format` renders a record on a single line, i.e. contains no newline.
+    is_single_line: bool,
+    /// The name of the input that is being decoded, for the `{source}` specifier.
+    source_name: Option<String>,
+    /// What the `{t}` specifier prints.
+    timestamp_mode: TimestampMode,
+    /// The minimum time between two frames that highlights the timestamps of the second one.
+    highlight_gaps: Option<Duration>,
+    timestamps: Mutex<TimestampHistory>,
+    /// When the first defmt frame was formatted; the `{E}` specifier prints the time elapsed since.
+    first_frame: OnceLock<Instant>,
+}
+
+/// The timestamps of the frames that were formatted, for relative and delta timestamps.
+///
+/// The frames of each core are tracked separately, since their clocks don't have to agree.
+#[derive(Debug, Default)]
+struct TimestampHistory {
+    cores: HashMap<Option<u8>, CoreTimestamps>,
+    /// The core that logged the frame that is being formatted
+    current_core: Option<u8>,
+}
+
+#[derive(Debug, Default)]
+struct CoreTimestamps {
+    first: Option<Timestamp>,
+    previous: Option<Timestamp>,
+    /// The timestamp of the frame that is being formatted, or of the last one of this core
+    current: Option<Timestamp>,
+}
+
+impl TimestampHistory {
+    /// Moves on to the frame with `timestamp`, logged by `core_id`; after a device reset, the
+    /// times of that core start over.
+    fn advance(&mut self, core_id: Option<u8>, timestamp: Option<Timestamp>, follows_reset: bool) {
+        self.current_core = core_id;
+        let core = self.cores.entry(core_id).or_default();
+        core.previous = core.current.take();
+        core.current = timestamp;
+        if core.first.is_none() || follows_reset {
+            core.first = timestamp;
+            core.previous = None;
+        }
+    }
+
+    /// The time elapsed since the first frame of the current core.
+    fn relative(&self) -> Option<Timestamp> {
+        let core = self.cores.get(&self.current_core)?;
+        elapsed(core.first?, core.current?)
+    }
+
+    /// The time elapsed since the previous frame of the current core, which is zero for its first
+    /// frame.
+    fn delta(&self) -> Option<Timestamp> {
+        let core = self.cores.get(&self.current_core)?;
+        elapsed(core.previous.or(core.first)?, core.current?)
+    }
+}
+
+/// Returns the time from `start` to `end`, if they're in the same unit and `end` isn't earlier.
+fn elapsed(start: Timestamp, end: Timestamp) -> Option<Timestamp> {
+    if start.unit != end.unit {
+        return None;
+    }
+
+    let value = end.value.checked_sub(start.value)?;
+    Some(Timestamp {
+        value,
+        unit: end.unit,
+    })
+}
+
+/// Formats a time span given as a timestamp like the display hint of its unit would, e.g.
+/// 1500 µs as "0.001500".
+fn format_elapsed(elapsed: Timestamp) -> String {
+    let Timestamp { value, unit } = elapsed;
+    match unit {
+        TimestampUnit::Micros => format!("{}.{:06}", value / 1_000_000, value % 1_000_000),
+        TimestampUnit::Millis => format!("{}.{:03}", value / 1_000, value % 1_000),
+        TimestampUnit::Seconds | TimestampUnit::Ticks => value.to_string(),
+    }
+}
+
+/// What the `{t}` log format specifier prints.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimestampMode {
+    /// The timestamp of the frame, like `{t}` does by default
+    #[default]
+    Absolute,
+    /// The time elapsed since the first frame, like `{tr}`
+    Relative,
+    /// The time elapsed since the previous frame, like `{td}`
+    Delta,
+    /// The estimated UTC wall-clock time, like `{W}`
+    WallClock,
+}
+
+impl FromStr for TimestampMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absolute" => Ok(TimestampMode::Absolute),
+            "relative" => Ok(TimestampMode::Relative),
+            "delta" => Ok(TimestampMode::Delta),
+            "wall-clock" => Ok(TimestampMode::WallClock),
+            _ => anyhow::bail!(
+                "unknown timestamp mode '{}', expected 'absolute', 'relative', 'delta' or \
+                'wall-clock'",
+                s
+            ),
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq)]
@@ -375,6 +588,11 @@ pub struct FormatterConfig<'a> {
     /// Not all targets can supply a timestamp, and if not, it should be
     /// omitted.
     pub is_timestamp_available: bool,
+    /// What the `{t}` specifier prints.
+    pub timestamps: TimestampMode,
+    /// Highlights the timestamps of frames that were logged at least this long after the previous
+    /// frame, according to the timestamps.
+    pub highlight_gaps: Option<Duration>,
 }
 
 impl<'a> FormatterConfig<'a> {
@@ -387,6 +605,8 @@ impl<'a> FormatterConfig<'a> {
             format: FormatterFormat::from_string(format, true)
                 .unwrap_or(FormatterFormat::Custom(format)),
             is_timestamp_available: false,
+            timestamps: TimestampMode::Absolute,
+            highlight_gaps: None,
         }
     }
 
@@ -397,6 +617,19 @@ impl<'a> FormatterConfig<'a> {
         self
     }
 
+    /// Modify a formatter configuration, setting what the `{t}` specifier prints.
+    pub fn with_timestamps(mut self, timestamps: TimestampMode) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    /// Modify a formatter configuration, highlighting the timestamps of frames that were logged
+    /// at least `gap` after the previous frame.
+    pub fn with_highlight_gaps(mut self, gap: Duration) -> Self {
+        self.highlight_gaps = Some(gap);
+        self
+    }
+
     /// Modify a formatter configuration, setting the 'with_location' flag
     /// to true.
     ///
@@ -468,10 +701,26 @@ impl InternalFormatter {
             }
         }
 
-        Self { format }
+        let is_single_line = !format_has_newline(&format);
+        Self {
+            format,
+            is_single_line,
+            source_name: None,
+            timestamp_mode: config.timestamps,
+            highlight_gaps: config.highlight_gaps,
+            timestamps: Mutex::new(TimestampHistory::default()),
+            first_frame: OnceLock::new(),
+        }
     }
 
     fn format(&self, record: &Record) -> String {
+        if let Record::Defmt(record) = record {
+            self.first_frame.get_or_init(Instant::now);
+            if let Some(buf) = self.format_multi_line(record) {
+                return buf;
+            }
+        }
+
         let mut buf = String::new();
         // Only format logs, not printlns
         // printlns do not have a log level
@@ -488,10 +737,56 @@ impl InternalFormatter {
         buf
     }
 
+    /// Formats a multi-line log message with a single-line format.
+    ///
+    /// Only the first line of the message is put into the format, so that the location footer of
+    /// formats like `oneline` stays next to it. The remaining lines, e.g. of a `:hexdump`, follow
+    /// unchanged below.
+    ///
+    /// Returns `None` if the record doesn't need this treatment.
+    fn format_multi_line(&self, record: &DefmtRecord) -> Option<String> {
+        if !self.is_single_line || record.level().is_none() {
+            return None;
+        }
+
+        let message = record.args().to_string();
+        let (first_line, rest) = message.split_once('\n')?;
+        // `defmt::assert_eq` output gets colored as a whole
+        if color_diff(&message).is_some() {
+            return None;
+        }
+
+        let mut buf = with_defmt_record(
+            format_args!("{first_line}"),
+            record.module_path(),
+            record.file(),
+            record.line(),
+            record.payload.clone(),
+            |first_line| self.format(&Record::Defmt(first_line)),
+        );
+        buf.push('\n');
+        buf.push_str(rest);
+        Some(buf)
+    }
+
     fn build_segment(&self, record: &Record, segment: &LogSegment) -> String {
         match &segment.metadata {
             LogMetadata::String(s) => s.to_string(),
             LogMetadata::Timestamp => self.build_timestamp(record, &segment.format),
+            LogMetadata::RelativeTimestamp => {
+                self.build_relative_timestamp(record, &segment.format)
+            }
+            LogMetadata::DeltaTimestamp => self.build_delta_timestamp(record, &segment.format),
+            LogMetadata::CoreId => self.build_core_id(record, &segment.format),
+            LogMetadata::HostTimestamp(time_format) => {
+                self.build_host_timestamp(record, time_format.as_ref(), &segment.format)
+            }
+            LogMetadata::WallClock(time_format) => {
+                self.build_wall_clock(record, time_format.as_ref(), &segment.format)
+            }
+            LogMetadata::HostElapsed => self.build_host_elapsed(record, &segment.format),
+            LogMetadata::FrameIndex => self.build_frame_index(record, &segment.format),
+            LogMetadata::SourceName => self.build_source_name(record, &segment.format),
             LogMetadata::CrateName => self.build_crate_name(record, &segment.format),
             LogMetadata::FileName(n) => self.build_file_name(record, &segment.format, *n),
             LogMetadata::FilePath => self.build_file_path(record, &segment.format),
@@ -511,6 +806,20 @@ impl InternalFormatter {
             let s = match &segment.metadata {
                 LogMetadata::String(s) => s.to_string(),
                 LogMetadata::Timestamp => self.build_timestamp(record, &segment.format),
+                LogMetadata::RelativeTimestamp => {
+                    self.build_relative_timestamp(record, &segment.format)
+                }
+                LogMetadata::DeltaTimestamp => self.build_delta_timestamp(record, &segment.format),
+                LogMetadata::CoreId => self.build_core_id(record, &segment.format),
+                LogMetadata::HostTimestamp(time_format) => {
+                    self.build_host_timestamp(record, time_format.as_ref(), &segment.format)
+                }
+                LogMetadata::WallClock(time_format) => {
+                    self.build_wall_clock(record, time_format.as_ref(), &segment.format)
+                }
+                LogMetadata::HostElapsed => self.build_host_elapsed(record, &segment.format),
+                LogMetadata::FrameIndex => self.build_frame_index(record, &segment.format),
+                LogMetadata::SourceName => self.build_source_name(record, &segment.format),
                 LogMetadata::CrateName => self.build_crate_name(record, &segment.format),
                 LogMetadata::FileName(n) => self.build_file_name(record, &segment.format, *n),
                 LogMetadata::FilePath => self.build_file_path(record, &segment.format),
@@ -535,12 +844,95 @@ impl InternalFormatter {
     }
 
     fn build_timestamp(&self, record: &Record, format: &LogFormat) -> String {
+        match self.timestamp_mode {
+            TimestampMode::Absolute => {}
+            TimestampMode::Relative => return self.build_relative_timestamp(record, format),
+            TimestampMode::Delta => return self.build_delta_timestamp(record, format),
+            TimestampMode::WallClock => return self.build_wall_clock(record, None, format),
+        }
+
         let s = match record {
             Record::Defmt(record) if !record.timestamp().is_empty() => record.timestamp(),
             _ => "<time>",
         }
         .to_string();
 
+        self.build_timestamp_string(&s, record, format)
+    }
+
+    fn build_relative_timestamp(&self, record: &Record, format: &LogFormat) -> String {
+        let relative = match record {
+            Record::Defmt(_) => self.timestamps.lock().unwrap().relative(),
+            Record::Host(_) => None,
+        };
+        let s = relative.map_or_else(|| "<time>".to_string(), format_elapsed);
+
+        self.build_timestamp_string(&s, record, format)
+    }
+
+    fn build_delta_timestamp(&self, record: &Record, format: &LogFormat) -> String {
+        let delta = match record {
+            Record::Defmt(_) => self.timestamps.lock().unwrap().delta(),
+            Record::Host(_) => None,
+        };
+        let s = match delta {
+            Some(delta) => format!("+{}", format_elapsed(delta)),
+            None => "<time>".to_string(),
+        };
+
+        self.build_timestamp_string(&s, record, format)
+    }
+
+    fn build_wall_clock(
+        &self,
+        record: &Record,
+        time_format: Option<&OwnedFormatItem>,
+        format: &LogFormat,
+    ) -> String {
+        let wall_clock = match record {
+            Record::Defmt(record) => record.wall_clock(),
+            Record::Host(_) => None,
+        };
+        let s = match wall_clock {
+            Some(wall_clock) => format_time(wall_clock, time_format, DEFAULT_WALL_CLOCK_FORMAT),
+            None => "<time>".to_string(),
+        };
+
+        self.build_timestamp_string(&s, record, format)
+    }
+
+    /// Formats a timestamp, in [`GAP_COLOR`] if the frame follows a gap.
+    fn build_timestamp_string(&self, s: &str, record: &Record, format: &LogFormat) -> String {
+        let color = match record {
+            Record::Defmt(_) if self.follows_gap() => Some(LogColor::Color(GAP_COLOR)),
+            _ => format.color,
+        };
+
+        build_formatted_string(s, format, 0, get_log_level_of_record(record), color)
+    }
+
+    /// Whether the frame that is being formatted was logged at least
+    /// [`FormatterConfig::highlight_gaps`] after the previous one.
+    fn follows_gap(&self) -> bool {
+        let Some(min_gap) = self.highlight_gaps else {
+            return false;
+        };
+
+        let delta = self.timestamps.lock().unwrap().delta();
+        delta
+            .and_then(|delta| delta.to_duration())
+            .is_some_and(|delta| delta >= min_gap)
+    }
+
+    fn build_core_id(&self, record: &Record, format: &LogFormat) -> String {
+        let s = match record {
+            Record::Defmt(record) => match record.core_id() {
+                Some(core_id) => core_id.to_string(),
+                None => "<core>".to_string(),
+            },
+            Record::Host(_) => "<core>".to_string(),
+        };
+
         build_formatted_string(
             s.as_str(),
             format,
@@ -550,6 +942,64 @@ impl InternalFormatter {
         )
     }
 
+    fn build_host_timestamp(
+        &self,
+        record: &Record,
+        time_format: Option<&OwnedFormatItem>,
+        format: &LogFormat,
+    ) -> String {
+        let s = format_time(SystemTime::now(), time_format, DEFAULT_HOST_TIME_FORMAT);
+
+        build_formatted_string(
+            s.as_str(),
+            format,
+            0,
+            get_log_level_of_record(record),
+            format.color,
+        )
+    }
+
+    fn build_host_elapsed(&self, record: &Record, format: &LogFormat) -> String {
+        let elapsed = self
+            .first_frame
+            .get()
+            .map(|first_frame| first_frame.elapsed())
+            .unwrap_or_default();
+        let s = format!("{}.{:03}", elapsed.as_secs(), elapsed.subsec_millis());
+
+        build_formatted_string(
+            s.as_str(),
+            format,
+            0,
+            get_log_level_of_record(record),
+            format.color,
+        )
+    }
+
+    fn build_frame_index(&self, record: &Record, format: &LogFormat) -> String {
+        let s = match record {
+            Record::Defmt(record) => match record.index() {
+                Some(index) => index.to_string(),
+                None => "<index>".to_string(),
+            },
+            Record::Host(_) => "<index>".to_string(),
+        };
+
+        build_formatted_string(
+            s.as_str(),
+            format,
+            0,
+            get_log_level_of_record(record),
+            format.color,
+        )
+    }
+
+    fn build_source_name(&self, record: &Record, format: &LogFormat) -> String {
+        let s = self.source_name.as_deref().unwrap_or("<source>");
+
+        build_formatted_string(s, format, 0, get_log_level_of_record(record), format.color)
+    }
+
     fn build_log_level(&self, record: &Record, format: &LogFormat) -> String {
         let s = match get_log_level_of_record(record) {
             Some(level) => level.to_string(),
@@ -665,15 +1115,45 @@ impl InternalFormatter {
     fn build_log(&self, record: &Record, format: &LogFormat) -> String {
         let log_level = get_log_level_of_record(record);
         match record {
-            Record::Defmt(record) => match color_diff(record.args().to_string()) {
-                Ok(s) => s.to_string(),
-                Err(s) => build_formatted_string(s.as_str(), format, 0, log_level, format.color),
-            },
+            Record::Defmt(record) => {
+                let mut message = "  ".repeat(record.span_depth());
+                message.push_str(&record.args().to_string());
+                if !record.fields().is_empty() {
+                    message.push(' ');
+                    message.push_str(&join_fields(record.fields()));
+                }
+                match color_diff(&message) {
+                    Some(s) => s,
+                    None => build_formatted_string(&message, format, 0, log_level, format.color),
+                }
+            }
             Record::Host(record) => record.args().to_string(),
         }
     }
 }
 
+/// Builds a [`DefmtRecord`] with the message `args` and passes it to `f`.
+fn with_defmt_record<R>(
+    args: fmt::Arguments<'_>,
+    module_path: Option<&str>,
+    file: Option<&str>,
+    line: Option<u32>,
+    payload: Payload,
+    f: impl FnOnce(&DefmtRecord) -> R,
+) -> R {
+    let log_record = &LogRecord::builder()
+        .args(args)
+        .module_path(module_path)
+        .file(file)
+        .line(line)
+        .build();
+
+    f(&DefmtRecord {
+        log_record,
+        payload,
+    })
+}
+
 fn get_log_level_of_record(record: &Record) -> Option<Level> {
     match record {
         Record::Defmt(record) => record.level(),
@@ -684,7 +1164,7 @@ fn get_log_level_of_record(record: &Record) -> Option<Level> {
 // color the output of `defmt::assert_eq`
 // HACK we should not re-parse formatted output but instead directly format into a color diff
 // template; that may require specially tagging log messages that come from `defmt::assert_eq`
-fn color_diff(text: String) -> Result<String, String> {
+fn color_diff(text: &str) -> Option<String> {
     let lines = text.lines().collect::<Vec<_>>();
     let nlines = lines.len();
     if nlines > 2 {
@@ -742,11 +1222,11 @@ fn color_diff(text: String) -> Result<String, String> {
                     }
                 }
             }
-            return Ok(buf);
+            return Some(buf);
         }
     }
 
-    Err(text)
+    None
 }
 
 fn color_for_log_level(level: Level) -> Color {
@@ -803,6 +1283,20 @@ fn apply_styles(s: ColoredString, log_style: Option<&Vec<Styles>>) -> ColoredStr
     stylized_string
 }
 
+/// Formats `time` in UTC with `time_format`, or with `default` if there is none.
+fn format_time(
+    time: SystemTime,
+    time_format: Option<&OwnedFormatItem>,
+    default: &[BorrowedFormatItem<'_>],
+) -> String {
+    let date_time = OffsetDateTime::from(time);
+    let formatted = match time_format {
+        Some(time_format) => date_time.format(time_format),
+        None => date_time.format(default),
+    };
+    formatted.unwrap_or_else(|_| "<time>".to_string())
+}
+
 fn build_formatted_string(
     s: &str,
     format: &LogFormat,
@@ -838,7 +1332,10 @@ fn build_formatted_string(
 fn format_has_timestamp(segments: &[LogSegment]) -> bool {
     for segment in segments {
         match &segment.metadata {
-            LogMetadata::Timestamp => return true,
+            LogMetadata::Timestamp
+            | LogMetadata::RelativeTimestamp
+            | LogMetadata::DeltaTimestamp
+            | LogMetadata::WallClock(_) => return true,
             LogMetadata::NestedLogSegments(s) => {
                 if format_has_timestamp(s) {
                     return true;
@@ -850,6 +1347,14 @@ fn format_has_timestamp(segments: &[LogSegment]) -> bool {
     false
 }
 
+fn format_has_newline(segments: &[LogSegment]) -> bool {
+    segments.iter().any(|segment| match &segment.metadata {
+        LogMetadata::String(s) => s.contains('\n'),
+        LogMetadata::NestedLogSegments(s) => format_has_newline(s),
+        _ => false,
+    })
+}
+
 /// Returns the given string excluding ANSI control sequences.
 fn string_excluding_ansi(s: &str) -> String {
     // Regular expression to match ANSI escape sequences
@@ -862,6 +1367,8 @@ fn string_excluding_ansi(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{clock_sync::ClockSync, Encoding, Table, TableEntry, Tag};
+    use std::time::SystemTime;
 
     #[test]
     fn test_left_aligned_styled_string() {
@@ -892,4 +1399,283 @@ mod tests {
         let string_without_styles = string_excluding_ansi(&s);
         assert_eq!(string_without_styles, "      test");
     }
+
+    fn format_frame(config: FormatterConfig, format: &str) -> String {
+        let table = Table {
+            timestamp: None,
+            core_id: None,
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, format.into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let frame = table.decode(&[0, 0]).unwrap().0;
+
+        let s = Formatter::new(config).format_frame(
+            frame,
+            Some("src/main.rs"),
+            Some(7),
+            Some("app::main"),
+        );
+        string_excluding_ansi(&s)
+    }
+
+    #[test]
+    fn multi_line_message_default_format() {
+        let config = FormatterConfig::default().with_location();
+        assert_eq!(
+            format_frame(config, "dump\nline 1\nline 2"),
+            "INFO  dump\nline 1\nline 2\n└─ app::main @ src/main.rs:7   "
+        );
+    }
+
+    #[test]
+    fn multi_line_message_oneline_format() {
+        let config = FormatterConfig {
+            format: FormatterFormat::OneLine {
+                with_location: true,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            format_frame(config, "dump\nline 1\nline 2"),
+            "[INFO ] dump (app src/main.rs:7)\nline 1\nline 2"
+        );
+    }
+
+    #[test]
+    fn core_id() {
+        let table = Table {
+            timestamp: None,
+            core_id: Some(TableEntry::new_without_symbol(Tag::CoreId, "{=u8}".into())),
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let frame = table.decode(&[0, 0, 1]).unwrap().0;
+        let config = FormatterConfig::custom("[{core}] {s}");
+        let s = Formatter::new(config).format_frame(frame, None, None, None);
+        assert_eq!(string_excluding_ansi(&s), "[1] hello");
+
+        // firmware without `defmt::core_id!`
+        let config = FormatterConfig::custom("[{core}] {s}");
+        assert_eq!(format_frame(config, "hello"), "[<core>] hello");
+    }
+
+    #[test]
+    fn fields() {
+        let table = Table {
+            timestamp: None,
+            core_id: None,
+            entries: [
+                (
+                    0,
+                    TableEntry::new_without_symbol(Tag::Info, "connected{@rssi}".into()),
+                ),
+                (1, TableEntry::new_without_symbol(Tag::Prim, "{=i8}".into())),
+            ]
+            .into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let frame = table.decode(&[0, 0, 1, 0, 0xcc]).unwrap().0;
+        let config = FormatterConfig::custom("{L} {s}");
+        let s = Formatter::new(config).format_frame(frame, None, None, None);
+        assert_eq!(string_excluding_ansi(&s), "INFO  connected rssi=-52");
+    }
+
+    #[test]
+    fn span_indentation() {
+        let table = Table {
+            timestamp: None,
+            core_id: None,
+            entries: [
+                (
+                    0,
+                    TableEntry::new_without_symbol(Tag::SpanEnter, "boot".into()),
+                ),
+                (1, TableEntry::new_without_symbol(Tag::Info, "hello".into())),
+            ]
+            .into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let mut stream_decoder = table.new_stream_decoder();
+        stream_decoder.received(&[0, 0, 1, 0]);
+        let formatter = Formatter::new(FormatterConfig::custom("[{L}] {s}"));
+
+        let enter = stream_decoder.decode().unwrap();
+        let s = formatter.format_frame(enter, None, None, None);
+        assert_eq!(string_excluding_ansi(&s), "[INFO ] -> boot");
+
+        let nested = stream_decoder.decode().unwrap();
+        let s = formatter.format_frame(nested, None, None, None);
+        assert_eq!(string_excluding_ansi(&s), "[INFO ]   hello");
+    }
+
+    #[test]
+    fn host_metadata() {
+        let table = Table {
+            timestamp: None,
+            core_id: None,
+            entries: [(3, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let frame = table.decode(&[3, 0]).unwrap().0;
+
+        let config = FormatterConfig::custom("{source} #{i} {T([year])} +{E} {s}");
+        let s = Formatter::new(config)
+            .with_source_name("uart0")
+            .format_frame(frame, None, None, None);
+        let s = string_excluding_ansi(&s);
+
+        let re = Regex::new(r"^uart0 #3 \d{4} \+\d+\.\d{3} hello$").unwrap();
+        assert!(re.is_match(&s), "{s}");
+    }
+
+    #[test]
+    fn relative_and_delta_timestamps() {
+        let table = Table {
+            timestamp: Some(TableEntry::new_without_symbol(
+                Tag::Timestamp,
+                "{=u32:us}".into(),
+            )),
+            core_id: None,
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let frames = [1_000u32, 1_500, 301_500].map(|timestamp| {
+            let mut bytes = vec![0, 0];
+            bytes.extend(timestamp.to_le_bytes());
+            bytes
+        });
+
+        let config = FormatterConfig::custom("{t} {tr} {td} {s}").with_timestamp();
+        let formatter = Formatter::new(config);
+        let lines = frames
+            .iter()
+            .map(|bytes| {
+                let frame = table.decode(bytes).unwrap().0;
+                string_excluding_ansi(&formatter.format_frame(frame, None, None, None))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "0.001000 0.000000 +0.000000 hello",
+                "0.001500 0.000500 +0.000500 hello",
+                "0.301500 0.300500 +0.300000 hello",
+            ]
+        );
+
+        // the default formats print `{t}` according to the timestamp mode
+        let config = FormatterConfig::default()
+            .with_timestamp()
+            .with_timestamps(TimestampMode::Delta)
+            .with_highlight_gaps(Duration::from_millis(100));
+        let formatter = Formatter::new(config);
+        let (lines, gaps): (Vec<_>, Vec<_>) = frames
+            .iter()
+            .map(|bytes| {
+                let frame = table.decode(bytes).unwrap().0;
+                let line = formatter.format_frame(frame, None, None, None);
+                (
+                    string_excluding_ansi(&line),
+                    formatter.formatter.follows_gap(),
+                )
+            })
+            .unzip();
+        assert_eq!(lines[1], "+0.000500 INFO  hello");
+        assert_eq!(gaps, [false, false, true]);
+    }
+
+    #[test]
+    fn delta_timestamps_per_core() {
+        let table = Table {
+            timestamp: Some(TableEntry::new_without_symbol(
+                Tag::Timestamp,
+                "{=u32:us}".into(),
+            )),
+            core_id: Some(TableEntry::new_without_symbol(Tag::CoreId, "{=u8}".into())),
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        let frames =
+            [(1_000u32, 0), (5_000, 1), (1_500, 0), (5_100, 1)].map(|(timestamp, core)| {
+                let mut bytes = vec![0, 0];
+                bytes.extend(timestamp.to_le_bytes());
+                bytes.push(core);
+                bytes
+            });
+
+        let config = FormatterConfig::custom("[{core}] {tr} {td} {s}");
+        let formatter = Formatter::new(config);
+        let lines = frames
+            .iter()
+            .map(|bytes| {
+                let frame = table.decode(bytes).unwrap().0;
+                string_excluding_ansi(&formatter.format_frame(frame, None, None, None))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "[0] 0.000000 +0.000000 hello",
+                "[1] 0.000000 +0.000000 hello",
+                "[0] 0.000500 +0.000500 hello",
+                "[1] 0.000100 +0.000100 hello",
+            ]
+        );
+    }
+
+    #[test]
+    fn wall_clock() {
+        let table = Table {
+            timestamp: Some(TableEntry::new_without_symbol(
+                Tag::Timestamp,
+                "{=u32:ms}".into(),
+            )),
+            core_id: None,
+            entries: [(0, TableEntry::new_without_symbol(Tag::Info, "hello".into()))].into(),
+            bitflags: Default::default(),
+            enums: Default::default(),
+            encoding: Encoding::Raw,
+        };
+        // logged 1 s after boot, and received at 2024-01-01T12:00:00Z
+        let frame = || {
+            let mut frame = table.decode(&[0, 0, 0xe8, 0x03, 0, 0]).unwrap().0;
+            let received = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_110_400);
+            ClockSync::default().update(&mut frame, received);
+            frame
+        };
+
+        let config = FormatterConfig::custom("{W} {W([hour]:[minute]:[second])} {s}");
+        let s = Formatter::new(config).format_frame(frame(), None, None, None);
+        assert_eq!(
+            string_excluding_ansi(&s),
+            "2024-01-01T12:00:00.000000Z 12:00:00 hello"
+        );
+
+        let config = FormatterConfig::default()
+            .with_timestamp()
+            .with_timestamps(TimestampMode::WallClock);
+        let s = Formatter::new(config).format_frame(frame(), None, None, None);
+        assert_eq!(
+            string_excluding_ansi(&s),
+            "2024-01-01T12:00:00.000000Z INFO  hello"
+        );
+
+        // frames that weren't passed to a `ClockSync`
+        let config = FormatterConfig::custom("{W} {s}");
+        assert_eq!(format_frame(config, "hello"), "<time> hello");
+    }
 }
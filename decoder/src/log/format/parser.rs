@@ -129,6 +129,7 @@ use nom::{
 };
 
 use std::str::FromStr;
+use time::format_description::{self, OwnedFormatItem};
 
 #[derive(Debug, PartialEq, Clone)]
 enum IntermediateOutput {
@@ -190,27 +191,52 @@ pub fn take_until_unbalanced(
 }
 
 fn parse_metadata(input: &str) -> IResult<&str, IntermediateOutput, ()> {
-    let mut parse_type = map_res(take_while(char::is_alphabetic), move |s| {
-        let metadata = match s {
-            "c" => LogMetadata::CrateName,
-            "F" => LogMetadata::FilePath,
-            "l" => LogMetadata::LineNumber,
-            "s" => LogMetadata::Log,
-            "L" => LogMetadata::LogLevel,
-            "m" => LogMetadata::ModulePath,
-            "t" => LogMetadata::Timestamp,
-            _ => {
-                if !s.is_empty() && s == "f".repeat(s.len()) {
-                    LogMetadata::FileName(s.len() as u8)
-                } else {
-                    return Err(());
-                }
+    let (input, s) = take_while(char::is_alphabetic)(input)?;
+    let metadata = match s {
+        "c" => LogMetadata::CrateName,
+        "core" => LogMetadata::CoreId,
+        "E" => LogMetadata::HostElapsed,
+        "F" => LogMetadata::FilePath,
+        "i" => LogMetadata::FrameIndex,
+        "l" => LogMetadata::LineNumber,
+        "s" => LogMetadata::Log,
+        "L" => LogMetadata::LogLevel,
+        "m" => LogMetadata::ModulePath,
+        "source" => LogMetadata::SourceName,
+        "t" => LogMetadata::Timestamp,
+        "tr" => LogMetadata::RelativeTimestamp,
+        "td" => LogMetadata::DeltaTimestamp,
+        "T" => {
+            let (input, time_format) = opt(parse_host_time_format)(input)?;
+            let metadata = LogMetadata::HostTimestamp(time_format);
+            return Ok((input, IntermediateOutput::Metadata(metadata)));
+        }
+        "W" => {
+            let (input, time_format) = opt(parse_host_time_format)(input)?;
+            let metadata = LogMetadata::WallClock(time_format);
+            return Ok((input, IntermediateOutput::Metadata(metadata)));
+        }
+        _ => {
+            if !s.is_empty() && s == "f".repeat(s.len()) {
+                LogMetadata::FileName(s.len() as u8)
+            } else {
+                return Err(nom::Err::Error(()));
             }
-        };
-        Ok(IntermediateOutput::Metadata(metadata))
-    });
+        }
+    };
 
-    parse_type.parse(input)
+    Ok((input, IntermediateOutput::Metadata(metadata)))
+}
+
+/// Parses the format description of a `{T}` or `{W}` specifier, e.g. `([hour]:[minute])`.
+fn parse_host_time_format(input: &str) -> IResult<&str, OwnedFormatItem, ()> {
+    let (input, time_format) =
+        delimited(char('('), take_until_unbalanced('(', ')'), char(')')).parse(input)?;
+
+    let time_format =
+        format_description::parse_owned::<1>(time_format).map_err(|_| nom::Err::Failure(()))?;
+
+    Ok((input, time_format))
 }
 
 fn parse_color(input: &str) -> IResult<&str, IntermediateOutput, ()> {
@@ -523,6 +549,73 @@ mod tests {
         assert_eq!(result, Ok(("", LogSegment::new(LogMetadata::Timestamp))));
     }
 
+    #[test]
+    fn test_parse_relative_and_delta_timestamp_arguments() {
+        let result = parse_argument::<false>("{tr}");
+        assert_eq!(
+            result,
+            Ok(("", LogSegment::new(LogMetadata::RelativeTimestamp)))
+        );
+
+        let result = parse_argument::<false>("{td}");
+        assert_eq!(
+            result,
+            Ok(("", LogSegment::new(LogMetadata::DeltaTimestamp)))
+        );
+    }
+
+    #[test]
+    fn test_parse_core_id_argument() {
+        let result = parse_argument::<false>("{core}");
+        assert_eq!(result, Ok(("", LogSegment::new(LogMetadata::CoreId))));
+    }
+
+    #[test]
+    fn test_parse_host_metadata_arguments() {
+        let result = parse_argument::<false>("{E}");
+        assert_eq!(result, Ok(("", LogSegment::new(LogMetadata::HostElapsed))));
+
+        let result = parse_argument::<false>("{i}");
+        assert_eq!(result, Ok(("", LogSegment::new(LogMetadata::FrameIndex))));
+
+        let result = parse_argument::<false>("{source}");
+        assert_eq!(result, Ok(("", LogSegment::new(LogMetadata::SourceName))));
+    }
+
+    #[test]
+    fn test_parse_wall_clock_argument() {
+        let result = parse_argument::<false>("{W}");
+        assert_eq!(
+            result,
+            Ok(("", LogSegment::new(LogMetadata::WallClock(None))))
+        );
+
+        let result = parse_argument::<false>("{W([hour]:[minute])}");
+        let time_format = format_description::parse_owned::<1>("[hour]:[minute]").unwrap();
+        let expected_output = LogSegment::new(LogMetadata::WallClock(Some(time_format)));
+        assert_eq!(result, Ok(("", expected_output)));
+    }
+
+    #[test]
+    fn test_parse_host_timestamp_argument() {
+        let result = parse_argument::<false>("{T}");
+        assert_eq!(
+            result,
+            Ok(("", LogSegment::new(LogMetadata::HostTimestamp(None))))
+        );
+
+        let result = parse_argument::<false>("{T([second].[subsecond digits:6]):bold}");
+        let time_format =
+            format_description::parse_owned::<1>("[second].[subsecond digits:6]").unwrap();
+        let expected_output = LogSegment::new(LogMetadata::HostTimestamp(Some(time_format)))
+            .with_style(colored::Styles::Bold);
+        assert_eq!(result, Ok(("", expected_output)));
+
+        // `[fortnight]` is not a component
+        let result = parse_argument::<false>("{T([fortnight])}");
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_parse_argument_with_color() {
         let result = parse_log_segment::<false>("t:werror");
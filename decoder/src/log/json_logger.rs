@@ -1,17 +1,25 @@
-use defmt_json_schema::v1::{JsonFrame, Location, ModulePath, SCHEMA_VERSION};
+use defmt_json_schema::v1::{
+    JsonFrame, Location, ModulePath, TargetTimestamp, TimestampUnit, SCHEMA_VERSION,
+};
 use log::{Log, Metadata, Record};
 use time::OffsetDateTime;
 
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    time::SystemTime,
+};
 
 use super::{
     format::{Formatter, HostFormatter},
     DefmtRecord, StdoutLogger,
 };
+use crate::Timestamp;
 
 pub(crate) struct JsonLogger {
     should_log: Box<dyn Fn(&Metadata) -> bool + Sync + Send>,
     host_logger: StdoutLogger,
+    /// Whether the frames include the boot counter
+    boot_counter: bool,
 }
 
 impl Log for JsonLogger {
@@ -28,10 +36,12 @@ impl Log for JsonLogger {
             // defmt goes to stdout, since it's the primary output produced by this tool.
             let mut sink = io::stdout().lock();
 
-            let host_timestamp = OffsetDateTime::now_utc()
-                .unix_timestamp_nanos()
-                .min(i64::MAX as i128) as i64;
-            serde_json::to_writer(&mut sink, &create_json_frame(record, host_timestamp)).ok();
+            let host_timestamp = unix_timestamp_nanos(SystemTime::now());
+            let mut json_frame = create_json_frame(&record, host_timestamp);
+            if self.boot_counter {
+                json_frame.boot = Some(record.boot());
+            }
+            serde_json::to_writer(&mut sink, &json_frame).ok();
             writeln!(sink).ok();
         } else {
             // non-defmt logs go to stderr
@@ -47,11 +57,13 @@ impl JsonLogger {
     pub fn new(
         formatter: Formatter,
         host_formatter: HostFormatter,
+        boot_counter: bool,
         should_log: impl Fn(&Metadata) -> bool + Sync + Send + 'static,
     ) -> Box<Self> {
         Box::new(Self {
             should_log: Box::new(should_log),
             host_logger: StdoutLogger::new_unboxed(formatter, host_formatter, |_| true),
+            boot_counter,
         })
     }
 
@@ -63,9 +75,10 @@ impl JsonLogger {
 }
 
 /// Create a new [JsonFrame] from a log-frame from the target
-fn create_json_frame(record: DefmtRecord, host_timestamp: i64) -> JsonFrame {
+fn create_json_frame(record: &DefmtRecord, host_timestamp: i64) -> JsonFrame {
     JsonFrame {
         data: record.args().to_string(),
+        fields: record.fields().iter().cloned().collect(),
         host_timestamp,
         level: record.level(),
         location: Location {
@@ -74,6 +87,28 @@ fn create_json_frame(record: DefmtRecord, host_timestamp: i64) -> JsonFrame {
             module_path: create_module_path(record.module_path()),
         },
         target_timestamp: record.timestamp().to_string(),
+        target_timestamp_value: record.timestamp_value().map(create_target_timestamp),
+        wall_clock_estimate: record.wall_clock().map(unix_timestamp_nanos),
+        boot: None,
+    }
+}
+
+/// Returns `time` as a Unix timestamp in nanoseconds.
+fn unix_timestamp_nanos(time: SystemTime) -> i64 {
+    OffsetDateTime::from(time)
+        .unix_timestamp_nanos()
+        .min(i64::MAX as i128) as i64
+}
+
+fn create_target_timestamp(timestamp: Timestamp) -> TargetTimestamp {
+    TargetTimestamp {
+        value: timestamp.value,
+        unit: match timestamp.unit {
+            crate::TimestampUnit::Micros => TimestampUnit::Us,
+            crate::TimestampUnit::Millis => TimestampUnit::Ms,
+            crate::TimestampUnit::Seconds => TimestampUnit::S,
+            crate::TimestampUnit::Ticks => TimestampUnit::Ticks,
+        },
     }
 }
 
@@ -12,7 +12,7 @@ pub mod format;
 mod json_logger;
 mod stdout_logger;
 
-use std::fmt;
+use std::{fmt, time::SystemTime};
 
 use log::{Level, LevelFilter, Log, Metadata, Record as LogRecord};
 use serde::{Deserialize, Serialize};
@@ -22,7 +22,7 @@ use self::{
     json_logger::JsonLogger,
     stdout_logger::StdoutLogger,
 };
-use crate::Frame;
+use crate::{Frame, Timestamp};
 
 const DEFMT_TARGET_MARKER: &str = "defmt@";
 
@@ -34,11 +34,24 @@ pub fn log_defmt(
     module_path: Option<&str>,
 ) {
     let (timestamp, level) = timestamp_and_level_from_frame(frame);
+    let core_id = frame.core_id();
 
     let target = format!(
         "{}{}",
         DEFMT_TARGET_MARKER,
-        serde_json::to_value(Payload { timestamp, level }).unwrap()
+        serde_json::to_value(Payload {
+            level,
+            timestamp,
+            timestamp_value: frame.timestamp(),
+            core_id,
+            index: Some(frame.index()),
+            fields: owned_fields(frame),
+            span_depth: frame.span_depth(),
+            wall_clock: frame.wall_clock(),
+            boot: frame.boot(),
+            follows_reset: frame.follows_reset(),
+        })
+        .unwrap()
     );
 
     log::logger().log(
@@ -74,12 +87,43 @@ pub struct DefmtLoggerConfig {
     pub formatter: Formatter,
     pub host_formatter: HostFormatter,
     pub logger_type: DefmtLoggerType,
+    /// Whether the JSON frames include the number of device resets detected before them
+    pub boot_counter: bool,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 struct Payload {
     level: Option<Level>,
     timestamp: String,
+    /// The timestamp as a number, see [`Frame::timestamp`]
+    #[serde(default)]
+    timestamp_value: Option<Timestamp>,
+    #[serde(default)]
+    core_id: Option<u8>,
+    #[serde(default)]
+    index: Option<u64>,
+    #[serde(default)]
+    fields: Vec<(String, String)>,
+    /// Number of `defmt::span!`s the frame is nested in
+    #[serde(default)]
+    span_depth: usize,
+    /// Estimated wall-clock time at which the frame was logged, see [`Frame::wall_clock`]
+    #[serde(default)]
+    wall_clock: Option<SystemTime>,
+    /// Number of device resets detected before the frame, see [`Frame::boot`]
+    #[serde(default)]
+    boot: u32,
+    #[serde(default)]
+    follows_reset: bool,
+}
+
+/// Returns the key-value fields of `frame`, for the [`Payload`].
+fn owned_fields(frame: &Frame<'_>) -> Vec<(String, String)> {
+    frame
+        .fields()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
 }
 
 impl<'a> DefmtRecord<'a> {
@@ -99,10 +143,50 @@ impl<'a> DefmtRecord<'a> {
         self.payload.timestamp.as_str()
     }
 
+    /// Returns the timestamp as a number, if the defmt timestamp is a single integer.
+    pub fn timestamp_value(&self) -> Option<Timestamp> {
+        self.payload.timestamp_value
+    }
+
     pub fn level(&self) -> Option<Level> {
         self.payload.level
     }
 
+    /// Returns the ID of the core that logged the frame, if known.
+    pub fn core_id(&self) -> Option<u8> {
+        self.payload.core_id
+    }
+
+    /// Returns the index of the frame's format string, if known.
+    pub fn index(&self) -> Option<u64> {
+        self.payload.index
+    }
+
+    /// Returns the key-value fields of the frame, which are not part of [`Self::args`].
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.payload.fields
+    }
+
+    /// Returns the number of `defmt::span!`s the frame is nested in.
+    pub fn span_depth(&self) -> usize {
+        self.payload.span_depth
+    }
+
+    /// Returns the estimated wall-clock time at which the frame was logged, if known.
+    pub fn wall_clock(&self) -> Option<SystemTime> {
+        self.payload.wall_clock
+    }
+
+    /// Returns the number of device resets that were detected before the frame.
+    pub fn boot(&self) -> u32 {
+        self.payload.boot
+    }
+
+    /// Returns whether this is the first frame after a detected device reset.
+    pub fn follows_reset(&self) -> bool {
+        self.payload.follows_reset
+    }
+
     pub fn args(&self) -> &fmt::Arguments<'a> {
         self.log_record.args()
     }
@@ -132,11 +216,31 @@ pub fn init_logger(
     logger_type: DefmtLoggerType,
     should_log: impl Fn(&Metadata) -> bool + Sync + Send + 'static,
 ) {
+    let config = DefmtLoggerConfig {
+        formatter,
+        host_formatter,
+        logger_type,
+        boot_counter: false,
+    };
+    init_logger_with_config(config, should_log)
+}
+
+/// Like [`init_logger`], but takes all settings from `config`.
+pub fn init_logger_with_config(
+    config: DefmtLoggerConfig,
+    should_log: impl Fn(&Metadata) -> bool + Sync + Send + 'static,
+) {
+    let DefmtLoggerConfig {
+        formatter,
+        host_formatter,
+        logger_type,
+        boot_counter,
+    } = config;
     let logger: Box<dyn Log> = match logger_type {
         DefmtLoggerType::Stdout => StdoutLogger::new(formatter, host_formatter, should_log),
         DefmtLoggerType::Json => {
             JsonLogger::print_schema_version();
-            JsonLogger::new(formatter, host_formatter, should_log)
+            JsonLogger::new(formatter, host_formatter, boot_counter, should_log)
         }
     };
     alterable_logger::set_boxed_logger(logger);
@@ -0,0 +1,63 @@
+//! Pairs the enter and exit frames of `defmt::span!`s.
+
+use std::collections::HashMap;
+
+use crate::{Frame, SpanEvent};
+
+/// Keeps track of the spans that are entered in a stream of frames.
+#[derive(Debug, Default)]
+pub(crate) struct SpanTracker {
+    /// Entered spans, innermost last, by core ID
+    entered: HashMap<Option<u8>, Vec<EnteredSpan>>,
+}
+
+#[derive(Debug)]
+struct EnteredSpan {
+    name: String,
+    /// Timestamp of the enter frame
+    ticks: Option<u64>,
+}
+
+impl SpanTracker {
+    /// Sets the span depth of `frame`, and how long the span lasted if it's an exit frame.
+    pub(crate) fn update(&mut self, frame: &mut Frame<'_>) {
+        let entered = self.entered.entry(frame.core_id()).or_default();
+        // the spans that were entered before a reset never exit
+        if frame.follows_reset() {
+            entered.clear();
+        }
+        match frame.span() {
+            Some(SpanEvent::Enter) => {
+                frame.set_span(entered.len(), None);
+                entered.push(EnteredSpan {
+                    name: span_name(frame),
+                    ticks: frame.timestamp().map(|timestamp| timestamp.value),
+                });
+            }
+            Some(SpanEvent::Exit) => {
+                let name = span_name(frame);
+                // spans whose exit frame got lost are exited along with their parent
+                match entered.iter().rposition(|span| span.name == name) {
+                    Some(depth) => {
+                        let enter_ticks = entered[depth].ticks;
+                        entered.truncate(depth);
+                        let elapsed = frame
+                            .timestamp()
+                            .map(|timestamp| timestamp.value)
+                            .zip(enter_ticks)
+                            .and_then(|(exit, enter)| exit.checked_sub(enter));
+                        frame.set_span(depth, elapsed);
+                    }
+                    None => frame.set_span(entered.len(), None),
+                }
+            }
+            None => frame.set_span(entered.len(), None),
+        }
+    }
+}
+
+/// Returns the name of the span, which is the message of its frames without the markers.
+fn span_name(frame: &Frame<'_>) -> String {
+    // span names have no parameters, and fields display as empty fragments
+    frame.display_fragments().collect()
+}
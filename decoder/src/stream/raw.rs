@@ -1,9 +1,11 @@
 use super::StreamDecoder;
-use crate::{DecodeError, Frame, Table};
+use crate::{span::SpanTracker, timestamp::TimestampTracker, DecodeError, Frame, Table};
 
 pub struct Raw<'a> {
     table: &'a Table,
     data: Vec<u8>,
+    timestamps: TimestampTracker,
+    spans: SpanTracker,
 }
 
 impl<'a> Raw<'a> {
@@ -11,6 +13,8 @@ impl<'a> Raw<'a> {
         Self {
             table,
             data: Vec::new(),
+            timestamps: TimestampTracker::default(),
+            spans: SpanTracker::default(),
         }
     }
 }
@@ -22,8 +26,10 @@ impl StreamDecoder for Raw<'_> {
 
     fn decode(&mut self) -> Result<Frame<'_>, DecodeError> {
         match self.table.decode(&self.data) {
-            Ok((frame, consumed)) => {
+            Ok((mut frame, consumed)) => {
                 self.data.drain(0..consumed);
+                self.timestamps.update(&mut frame);
+                self.spans.update(&mut frame);
                 Ok(frame)
             }
             Err(e) => Err(e),
@@ -1,5 +1,5 @@
 use super::StreamDecoder;
-use crate::{DecodeError, Frame, Table};
+use crate::{span::SpanTracker, timestamp::TimestampTracker, DecodeError, Frame, Table};
 
 /// Decode a full message.
 ///
@@ -42,6 +42,8 @@ fn rzcobs_decode(data: &[u8]) -> Result<Vec<u8>, DecodeError> {
 pub struct Rzcobs<'a> {
     table: &'a Table,
     raw: Vec<u8>,
+    timestamps: TimestampTracker,
+    spans: SpanTracker,
 }
 
 impl<'a> Rzcobs<'a> {
@@ -49,6 +51,8 @@ impl<'a> Rzcobs<'a> {
         Self {
             table,
             raw: Vec::new(),
+            timestamps: TimestampTracker::default(),
+            spans: SpanTracker::default(),
         }
     }
 }
@@ -87,7 +91,11 @@ impl StreamDecoder for Rzcobs<'_> {
 
         let frame: Vec<u8> = frame?;
         match self.table.decode(&frame) {
-            Ok((frame, _consumed)) => Ok(frame),
+            Ok((mut frame, _consumed)) => {
+                self.timestamps.update(&mut frame);
+                self.spans.update(&mut frame);
+                Ok(frame)
+            }
             Err(DecodeError::UnexpectedEof) => Err(DecodeError::Malformed),
             Err(DecodeError::Malformed) => Err(DecodeError::Malformed),
         }
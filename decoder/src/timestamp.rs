@@ -0,0 +1,65 @@
+//! Extends wrapping timestamps to 64 bits and detects device resets.
+
+use std::collections::HashMap;
+
+use crate::Frame;
+
+/// Which fraction of its range a timestamp may advance across a wraparound.
+///
+/// A timestamp that goes backwards further than that is taken as a device reset: e.g. a 32-bit
+/// microsecond timestamp wraps around every 71 minutes, so frames logged within 4 minutes before
+/// and after the wraparound are taken as such.
+const MAX_WRAP_FRACTION: u32 = 16;
+
+/// Keeps track of the timestamps in a stream of frames.
+#[derive(Debug, Default)]
+pub(crate) struct TimestampTracker {
+    /// The state of each core, by core ID
+    cores: HashMap<Option<u8>, Clock>,
+}
+
+#[derive(Debug, Default)]
+struct Clock {
+    /// The timestamp of the previous frame, as sent by the device
+    previous: Option<u64>,
+    /// Added to the timestamps for the wraparounds since the last reset
+    epoch: u64,
+    /// Number of resets
+    boot: u32,
+}
+
+impl TimestampTracker {
+    /// Extends the timestamp of `frame` past the wraparounds, and counts the resets before it.
+    pub(crate) fn update(&mut self, frame: &mut Frame<'_>) {
+        let Some((timestamp, bits)) = frame.device_timestamp() else {
+            return;
+        };
+        let clock = self.cores.entry(frame.core_id()).or_default();
+
+        let mut follows_reset = false;
+        if let Some(previous) = clock.previous {
+            if timestamp.value < previous {
+                match bits.filter(|bits| *bits < 64) {
+                    Some(bits) if is_wraparound(previous, timestamp.value, bits) => {
+                        clock.epoch += 1 << bits;
+                    }
+                    _ => {
+                        clock.epoch = 0;
+                        clock.boot += 1;
+                        follows_reset = true;
+                    }
+                }
+            }
+        }
+        clock.previous = Some(timestamp.value);
+
+        frame.set_timestamp_epoch(clock.epoch, clock.boot, follows_reset);
+    }
+}
+
+/// Whether a `bits` wide timestamp going back from `previous` to `current` is a wraparound.
+fn is_wraparound(previous: u64, current: u64, bits: u32) -> bool {
+    let range = 1u64 << bits;
+    let advance = current + range - previous;
+    advance <= range / u64::from(MAX_WRAP_FRACTION)
+}
@@ -1,11 +1,15 @@
 mod integers;
+mod rate_limit;
 mod traits;
 
 use core::fmt::Write as _;
 
 use crate::{Format, Formatter, Str};
 
-pub use self::integers::*;
+pub use self::{
+    integers::*,
+    rate_limit::{Every, Once, Throttled},
+};
 pub use bitflags::bitflags;
 
 pub trait UnsignedInt {}
@@ -15,10 +19,19 @@ impl UnsignedInt for u32 {}
 impl UnsignedInt for u64 {}
 impl UnsignedInt for u128 {}
 
+pub trait Int {}
+impl<T: UnsignedInt> Int for T {}
+impl Int for i8 {}
+impl Int for i16 {}
+impl Int for i32 {}
+impl Int for i64 {}
+impl Int for i128 {}
+
 #[cfg(feature = "unstable-test")]
 thread_local! {
     static I: core::sync::atomic::AtomicU16 = const { core::sync::atomic::AtomicU16::new(0) };
     static BYTES: core::cell::RefCell<Vec<u8>> = const { core::cell::RefCell::new(Vec::new()) };
+    static THROTTLE_CLOCK: core::cell::Cell<u32> = const { core::cell::Cell::new(0) };
 }
 
 /// For testing purposes
@@ -73,10 +86,12 @@ pub unsafe fn release() {
 
 #[cfg(feature = "unstable-test")]
 pub fn write(bytes: &[u8]) {
+    #[cfg(feature = "last-frame")]
+    crate::last_frame::record(bytes);
     BYTES.with(|b| b.borrow_mut().extend(bytes))
 }
 
-#[cfg(not(feature = "unstable-test"))]
+#[cfg(all(not(feature = "unstable-test"), not(feature = "last-frame")))]
 #[inline(always)]
 pub fn write(bytes: &[u8]) {
     extern "Rust" {
@@ -85,6 +100,17 @@ pub fn write(bytes: &[u8]) {
     unsafe { _defmt_write(bytes) }
 }
 
+// not inlined, so that keeping a copy of the frame doesn't grow every call site
+#[cfg(all(not(feature = "unstable-test"), feature = "last-frame"))]
+#[inline(never)]
+pub fn write(bytes: &[u8]) {
+    extern "Rust" {
+        fn _defmt_write(bytes: &[u8]);
+    }
+    crate::last_frame::record(bytes);
+    unsafe { _defmt_write(bytes) }
+}
+
 /// For testing purposes
 #[cfg(feature = "unstable-test")]
 pub fn timestamp(_fmt: crate::Formatter<'_>) {}
@@ -98,6 +124,40 @@ pub fn timestamp(fmt: crate::Formatter<'_>) {
     unsafe { _defmt_timestamp(fmt) }
 }
 
+/// For testing purposes
+#[cfg(feature = "unstable-test")]
+pub fn core_id(_fmt: crate::Formatter<'_>) {}
+
+#[cfg(not(feature = "unstable-test"))]
+#[inline(always)]
+pub fn core_id(fmt: crate::Formatter<'_>) {
+    extern "Rust" {
+        fn _defmt_core_id(_: crate::Formatter<'_>);
+    }
+    unsafe { _defmt_core_id(fmt) }
+}
+
+/// For testing purposes
+#[cfg(feature = "unstable-test")]
+pub fn throttle_clock() -> u32 {
+    THROTTLE_CLOCK.with(|clock| clock.get())
+}
+
+/// For testing purposes
+#[cfg(feature = "unstable-test")]
+pub fn set_throttle_clock(now: u32) {
+    THROTTLE_CLOCK.with(|clock| clock.set(now))
+}
+
+#[cfg(not(feature = "unstable-test"))]
+#[inline(always)]
+pub fn throttle_clock() -> u32 {
+    extern "Rust" {
+        fn _defmt_throttle_clock() -> u32;
+    }
+    unsafe { _defmt_throttle_clock() }
+}
+
 /// Returns the interned string at `address`.
 pub fn make_istr(address: u16) -> Str {
     Str { address }
@@ -118,9 +178,47 @@ pub fn into_result<T: traits::IntoResult>(x: T) -> Result<T::Ok, T::Error> {
     x.into_result()
 }
 
+/// Returns `true` if `module_path` is, or is inside of, a module that matches `pattern`.
+///
+/// A `*` in `pattern` matches any part of a path segment, but not the `::` separator. Used by
+/// the log macros to evaluate the glob patterns of `DEFMT_LOG` at compile time.
+pub const fn module_path_matches(module_path: &[u8], pattern: &[u8]) -> bool {
+    let (mut m, mut p) = (0, 0);
+    // where to resume when the last `*` has to match one more byte: (pattern, module path)
+    let mut backtrack = None;
+
+    loop {
+        if p == pattern.len() {
+            // the pattern matched; it must end at the end of a path segment
+            if m == module_path.len() || module_path[m] == b':' {
+                return true;
+            }
+        } else if pattern[p] == b'*' {
+            p += 1;
+            backtrack = Some((p, m));
+            continue;
+        } else if m < module_path.len() && module_path[m] == pattern[p] {
+            m += 1;
+            p += 1;
+            continue;
+        }
+
+        match backtrack {
+            Some((bp, bm)) if bm < module_path.len() && module_path[bm] != b':' => {
+                backtrack = Some((bp, bm + 1));
+                p = bp;
+                m = bm + 1;
+            }
+            _ => return false,
+        }
+    }
+}
+
 /// For testing purposes
 #[cfg(feature = "unstable-test")]
 pub fn panic() -> ! {
+    #[cfg(feature = "last-frame")]
+    crate::last_frame::mark_panic();
     panic!()
 }
 
@@ -130,6 +228,8 @@ pub fn panic() -> ! {
     extern "Rust" {
         fn _defmt_panic() -> !;
     }
+    #[cfg(feature = "last-frame")]
+    crate::last_frame::mark_panic();
     unsafe { _defmt_panic() }
 }
 
@@ -211,20 +311,33 @@ pub fn display(val: &dyn core::fmt::Display) {
 #[inline(never)]
 pub unsafe fn acquire_and_header(s: &Str) {
     acquire();
-    istr(s);
-    timestamp(make_formatter());
+    header(s);
 }
 
 #[inline(never)]
 pub fn acquire_header_and_release(s: &Str) {
     // safety: will be released a few lines further down
     unsafe { acquire() };
-    istr(s);
-    timestamp(make_formatter());
+    header(s);
     // safety: acquire() was called a few lines above
     unsafe { release() };
 }
 
+/// Writes the header of a frame; the logger must be acquired.
+#[inline(always)]
+fn header(s: &Str) {
+    #[cfg(feature = "last-frame")]
+    crate::last_frame::start();
+    istr(s);
+    // the timestamp and core ID of a kept frame are replaced when it is logged again
+    #[cfg(feature = "last-frame")]
+    crate::last_frame::pause();
+    timestamp(make_formatter());
+    core_id(make_formatter());
+    #[cfg(feature = "last-frame")]
+    crate::last_frame::resume();
+}
+
 struct FmtWrite;
 
 impl core::fmt::Write for FmtWrite {
@@ -233,3 +346,26 @@ impl core::fmt::Write for FmtWrite {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::module_path_matches;
+
+    #[test]
+    fn module_path_matches_glob_patterns() {
+        let matches = |module_path: &str, pattern: &str| {
+            module_path_matches(module_path.as_bytes(), pattern.as_bytes())
+        };
+
+        assert!(matches("embassy_net", "embassy_*"));
+        assert!(matches("embassy_net::tcp", "embassy_*"));
+        assert!(matches("app::drivers::uart", "*::drivers::*"));
+        assert!(matches("app::drivers::uart::regs", "*::drivers::*"));
+        assert!(matches("nrf_hal_common::spim", "nrf*_common::spi*"));
+
+        assert!(!matches("app::drivers", "*::drivers::*"));
+        assert!(!matches("app::drivers2::uart", "*::drivers::*"));
+        assert!(!matches("app::hal::drivers::uart", "*::drivers::*"));
+        assert!(!matches("embassy", "embassy_*"));
+    }
+}
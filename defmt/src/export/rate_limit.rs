@@ -0,0 +1,95 @@
+//! Per call site state of the rate-limited log macros, like `info_once!`.
+//!
+//! On targets without compare-and-swap, e.g. `thumbv6m-none-eabi`, the state is read and written
+//! in two steps, so if an interrupt handler logs from the same call site in between, an extra
+//! frame can get through.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// State of an `*_once!` call site.
+pub struct Once {
+    logged: AtomicU32,
+}
+
+impl Once {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            logged: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns `true` the first time it is called.
+    pub fn should_log(&self) -> bool {
+        update(&self.logged, |logged| (logged == 0).then_some(1))
+    }
+}
+
+/// State of an `*_every!` call site.
+pub struct Every {
+    /// Number of calls to skip before the next frame is logged
+    skip: AtomicU32,
+}
+
+impl Every {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            skip: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns `true` for the first of every `n` calls.
+    pub fn should_log(&self, n: u32) -> bool {
+        let mut log = false;
+        update(&self.skip, |skip| {
+            log = skip == 0;
+            Some(if log { n.saturating_sub(1) } else { skip - 1 })
+        });
+        log
+    }
+}
+
+/// State of an `*_throttled!` call site.
+pub struct Throttled {
+    /// Clock reading when the last frame was logged, or 0 if none was logged yet
+    last: AtomicU32,
+}
+
+impl Throttled {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU32::new(0),
+        }
+    }
+
+    /// Returns `true` if no frame was logged within the last `period` ticks of the throttle clock.
+    pub fn should_log(&self, period: u32) -> bool {
+        let now = super::throttle_clock();
+        update(&self.last, |last| {
+            // 0 means "never"; logging at tick 0 is recorded as tick 1, which is close enough
+            (last == 0 || now.wrapping_sub(last) >= period).then_some(now.max(1))
+        })
+    }
+}
+
+/// Applies `f` to the value of `atomic`, unless it returns `None`; returns whether it was applied.
+#[cfg(target_has_atomic = "32")]
+fn update(atomic: &AtomicU32, f: impl FnMut(u32) -> Option<u32>) -> bool {
+    atomic
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, f)
+        .is_ok()
+}
+
+/// Applies `f` to the value of `atomic`, unless it returns `None`; returns whether it was applied.
+#[cfg(not(target_has_atomic = "32"))]
+fn update(atomic: &AtomicU32, mut f: impl FnMut(u32) -> Option<u32>) -> bool {
+    match f(atomic.load(Ordering::Relaxed)) {
+        Some(value) => {
+            atomic.store(value, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,119 @@
+//! A copy of the last log frame, e.g. for a panic handler that stores the panic message.
+//!
+//! With the `last-frame` feature, every frame is copied into a small RAM buffer while it is logged,
+//! leaving out the timestamp and core ID. After `defmt::panic!`, `defmt::unwrap!`,
+//! `defmt::assert!` and the like, the copy is the frame with the panic message, and [`is_panic`]
+//! returns `true`. A panic handler can [`copy`] it into memory that survives a reset, and the next
+//! boot can [`log`] it again.
+//!
+//! The frame only contains the indices of the interned strings, so it can only be decoded with the
+//! ELF file of the firmware that logged it.
+//!
+//! Frames that don't fit into the buffer (128 bytes) aren't kept. On multi-core devices, frames
+//! logged at the same time on several cores overwrite each other's copy.
+
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+use crate::export;
+
+/// Maximum size of a kept frame, without timestamp and core ID
+const CAPACITY: usize = 128;
+/// Value of `LEN` after a frame didn't fit into `FRAME`
+const OVERFLOW: usize = usize::MAX;
+
+/// The bytes of the last frame, only valid up to `LEN`
+static FRAME: [AtomicU8; CAPACITY] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicU8 = AtomicU8::new(0);
+    [ZERO; CAPACITY]
+};
+/// Number of bytes in `FRAME`, or `OVERFLOW`
+static LEN: AtomicUsize = AtomicUsize::new(0);
+/// Is `false` while the timestamp and core ID are written, which aren't kept
+static RECORDING: AtomicBool = AtomicBool::new(false);
+/// Whether the last frame was logged right before a panic
+static PANIC: AtomicBool = AtomicBool::new(false);
+
+/// Copies the last frame into `buf` and returns its length, or `None` if no complete frame is kept
+/// or `buf` is too small.
+///
+/// The frame doesn't contain the timestamp and core ID.
+pub fn copy(buf: &mut [u8]) -> Option<usize> {
+    let len = LEN.load(Ordering::Relaxed);
+    if len == 0 || len > CAPACITY || len > buf.len() {
+        return None;
+    }
+    for (dst, src) in buf.iter_mut().zip(&FRAME[..len]) {
+        *dst = src.load(Ordering::Relaxed);
+    }
+    Some(len)
+}
+
+/// Returns whether the last frame was logged by `defmt::panic!` or a similar macro right before
+/// it panicked.
+pub fn is_panic() -> bool {
+    PANIC.load(Ordering::Relaxed)
+}
+
+/// Logs a frame returned by [`copy`] again, with the current timestamp and core ID.
+///
+/// The frame must come from the same firmware, otherwise it is decoded as garbage.
+pub fn log(frame: &[u8]) {
+    if frame.len() < 2 {
+        return;
+    }
+    let (index, data) = frame.split_at(2);
+
+    // safety: will be released a few lines further down
+    unsafe { export::acquire() };
+    start();
+    export::write(index);
+    pause();
+    export::timestamp(export::make_formatter());
+    export::core_id(export::make_formatter());
+    resume();
+    export::write(data);
+    // safety: acquire() was called a few lines above
+    unsafe { export::release() };
+}
+
+/// Starts a new frame; called once the logger is acquired.
+pub(crate) fn start() {
+    LEN.store(0, Ordering::Relaxed);
+    PANIC.store(false, Ordering::Relaxed);
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// Keeps `bytes` of the current frame, unless it is the timestamp or core ID.
+pub(crate) fn record(bytes: &[u8]) {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return;
+    }
+    let len = LEN.load(Ordering::Relaxed);
+    if len == OVERFLOW {
+        return;
+    }
+    let Some(end) = len.checked_add(bytes.len()).filter(|end| *end <= CAPACITY) else {
+        LEN.store(OVERFLOW, Ordering::Relaxed);
+        return;
+    };
+    for (dst, src) in FRAME[len..end].iter().zip(bytes) {
+        dst.store(*src, Ordering::Relaxed);
+    }
+    LEN.store(end, Ordering::Relaxed);
+}
+
+/// Stops keeping bytes of the current frame, e.g. while the timestamp is written.
+pub(crate) fn pause() {
+    RECORDING.store(false, Ordering::Relaxed);
+}
+
+/// Keeps the following bytes of the current frame again.
+pub(crate) fn resume() {
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// Marks the last frame as the message of the panic that is about to happen.
+pub(crate) fn mark_panic() {
+    PANIC.store(true, Ordering::Relaxed);
+}
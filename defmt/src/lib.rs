@@ -54,6 +54,11 @@ mod encoding;
 pub mod export;
 mod formatter;
 mod impls;
+#[cfg(feature = "last-frame")]
+pub mod last_frame;
+#[cfg(feature = "logger")]
+pub mod logger;
+mod span;
 #[cfg(all(test, feature = "unstable-test"))]
 mod tests;
 mod traits;
@@ -62,6 +67,7 @@ pub use crate::{
     encoding::Encoder,
     formatter::{Formatter, Str},
     impls::adapter::{Debug2Format, Display2Format},
+    span::Span,
     traits::{Format, Logger},
 };
 
@@ -274,6 +280,121 @@ pub use defmt_macros::trace;
 /// [the manual]: https://defmt.ferrous-systems.com/macros.html
 pub use defmt_macros::warn;
 
+/// Logs data at *debug* level, but only the first time this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::debug_once;
+/// Logs data at *error* level, but only the first time this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::error_once;
+/// Logs data at *info* level, but only the first time this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::info_once;
+/// Logs data at *trace* level, but only the first time this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::trace_once;
+/// Logs data at *warn* level, but only the first time this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::warn_once;
+
+/// Logs data at *debug* level, but only the first of every `n` times this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::debug_every;
+/// Logs data at *error* level, but only the first of every `n` times this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::error_every;
+/// Logs data at *info* level, but only the first of every `n` times this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::info_every;
+/// Logs data at *trace* level, but only the first of every `n` times this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::trace_every;
+/// Logs data at *warn* level, but only the first of every `n` times this call site is reached.
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::warn_every;
+
+/// Logs data at *debug* level, but at most once per `period` ticks of the [`throttle_clock!`].
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::debug_throttled;
+/// Logs data at *error* level, but at most once per `period` ticks of the [`throttle_clock!`].
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::error_throttled;
+/// Logs data at *info* level, but at most once per `period` ticks of the [`throttle_clock!`].
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::info_throttled;
+/// Logs data at *trace* level, but at most once per `period` ticks of the [`throttle_clock!`].
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::trace_throttled;
+/// Logs data at *warn* level, but at most once per `period` ticks of the [`throttle_clock!`].
+///
+/// Please refer to [the manual] for documentation on the syntax.
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::warn_throttled;
+
+/// Enters a span: logs an enter frame at *info* level and returns a [`Span`] guard, which logs
+/// the matching exit frame when dropped.
+///
+/// The decoder indents the frames logged while a span is entered, and prints how long the span
+/// lasted next to its exit frame, if the firmware defines a [`timestamp!`]. The span name must not
+/// contain formatting parameters; pass data as key-value fields instead.
+///
+/// Please refer to [the manual] for more details.
+///
+/// # Examples
+///
+/// ```
+/// # let sector = 3u32;
+/// {
+///     let _span = defmt::span!("flash_erase", sector = sector);
+///     // erase the sector
+/// } // the span is exited here
+/// ```
+///
+/// [the manual]: https://defmt.ferrous-systems.com/macros.html
+pub use defmt_macros::span;
+
 /// Just like the [`std::dbg!`] macro but `defmt` is used to log the message at `TRACE` level.
 ///
 /// [`std::dbg!`]: https://doc.rust-lang.org/std/macro.dbg.html
@@ -340,6 +461,46 @@ pub use defmt_macros::global_logger;
 /// ```
 pub use defmt_macros::timestamp;
 
+/// Defines the ID of the core that logs, for multi-core devices.
+///
+/// The ID, an `u8` expression, is included in every defmt message, after the timestamp. Printers
+/// can show it with the `{core}` log format specifier. Like [`timestamp!`], the expression is
+/// evaluated while the logger is acquired, and the macro should be placed in a module instead of a
+/// function.
+///
+/// `core_id!` must only be used once across the crate graph. If no crate defines a core ID, none
+/// is included in the logged messages.
+///
+/// # Examples
+///
+/// ```
+/// # fn current_core() -> usize { 0 }
+/// defmt::core_id!(current_core() as u8);
+/// ```
+pub use defmt_macros::core_id;
+
+/// Defines the clock of the `*_throttled!` log macros, like [`info_throttled!`].
+///
+/// The clock, an `u32` expression, counts ticks, e.g. milliseconds, and may wrap around. The
+/// macro should be placed in a module instead of a function.
+///
+/// `throttle_clock!` must only be used once across the crate graph, and must be used if any crate
+/// uses the `*_throttled!` macros, or linking fails.
+///
+/// # Examples
+///
+/// ```
+/// # use core::sync::atomic::{AtomicU32, Ordering};
+/// // incremented by a 1 kHz timer interrupt
+/// static MILLIS: AtomicU32 = AtomicU32::new(0);
+/// defmt::throttle_clock!(MILLIS.load(Ordering::Relaxed));
+///
+/// # fn on_overrun() {
+/// defmt::error_throttled!(1_000, "UART overrun"); // at most once per second
+/// # }
+/// ```
+pub use defmt_macros::throttle_clock;
+
 /// Generates a bitflags structure that can be formatted with defmt.
 ///
 /// This macro is a wrapper around the [`bitflags!`] crate, and provides an (almost) identical
@@ -372,6 +533,31 @@ pub use defmt_macros::timestamp;
 /// ```
 pub use defmt_macros::bitflags;
 
+/// Declares names for the values of an integer, for use with the `:enum(Name)` display hint.
+///
+/// This is useful for register values and error codes that are logged as plain integers. The
+/// names are stored in the ELF file, like the flags of [`bitflags!`], and are looked up by the
+/// decoder on the host. No type is generated.
+///
+/// The integer type must be one of Rust's built-in integer types (`u8` to `u128`, `i8` to `i128`).
+/// Values without a name are printed as plain integers.
+///
+/// # Examples
+///
+/// ```
+/// defmt::enum_names! {
+///     enum UsbStatus: u8 {
+///         Ok = 0,
+///         Stall = 1,
+///         Nak = 2,
+///     }
+/// }
+///
+/// let status = 1u8;
+/// defmt::info!("status: {=u8:enum(UsbStatus)}", status); // -> INFO status: Stall
+/// ```
+pub use defmt_macros::enum_names;
+
 #[doc(hidden)] // documented as the `Format` trait instead
 pub use defmt_macros::Format;
 
@@ -380,6 +566,10 @@ pub use defmt_macros::Format;
 #[export_name = "__defmt_default_timestamp"]
 fn default_timestamp(_f: Formatter<'_>) {}
 
+// Likewise, frames only contain a core ID if `core_id!` is used.
+#[export_name = "__defmt_default_core_id"]
+fn default_core_id(_f: Formatter<'_>) {}
+
 #[export_name = "__defmt_default_panic"]
 fn default_panic() -> ! {
     core::panic!()
@@ -0,0 +1,292 @@
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::Sink;
+use crate::Encoder;
+
+/// A global logger that encodes frames into a buffer of `N` bytes and writes them to a [`Sink`].
+///
+/// Install it as the global logger with [`buffered_logger!`](crate::buffered_logger).
+pub struct BufferedLogger<S, const N: usize> {
+    /// Is `true` when `acquire` has been called and we have exclusive access to the rest of this
+    /// structure.
+    taken: AtomicBool,
+    /// We need to remember this to exit a critical section
+    cs_restore: UnsafeCell<critical_section::RestoreState>,
+    encoder: UnsafeCell<Encoder>,
+    buffer: UnsafeCell<Buffer<N>>,
+    sink: UnsafeCell<S>,
+}
+
+// SAFETY: all accesses to the cells happen inside a critical section.
+unsafe impl<S: Send, const N: usize> Sync for BufferedLogger<S, N> {}
+
+impl<S: Sink, const N: usize> BufferedLogger<S, N> {
+    /// Creates a logger that writes to `sink`.
+    pub const fn new(sink: S) -> Self {
+        assert!(N > 0, "the buffer must not be empty");
+
+        Self {
+            taken: AtomicBool::new(false),
+            cs_restore: UnsafeCell::new(critical_section::RestoreState::invalid()),
+            encoder: UnsafeCell::new(Encoder::new()),
+            buffer: UnsafeCell::new(Buffer::new()),
+            sink: UnsafeCell::new(sink),
+        }
+    }
+
+    /// Gives exclusive access to the sink, e.g. to set it up after the logger has been created.
+    ///
+    /// Panics if called while a log frame is being written, e.g. from a `Format` impl.
+    pub fn with_sink<R>(&self, f: impl FnOnce(&mut S) -> R) -> R {
+        critical_section::with(|_| {
+            if self.taken.load(Ordering::Relaxed) {
+                panic!("defmt logger taken reentrantly")
+            }
+
+            // safety: accessing the cell is OK because we are in a critical section and the logger
+            // isn't acquired.
+            f(unsafe { &mut *self.sink.get() })
+        })
+    }
+
+    /// Implementation of [`Logger::acquire`](crate::Logger::acquire).
+    pub fn acquire(&self) {
+        // safety: Must be paired with corresponding call to release(), see below
+        let restore = unsafe { critical_section::acquire() };
+
+        // NB: You can re-enter critical sections but we need to make sure
+        // no-one does that.
+        if self.taken.load(Ordering::Relaxed) {
+            panic!("defmt logger taken reentrantly")
+        }
+
+        // no need for CAS because we are in a critical section
+        self.taken.store(true, Ordering::Relaxed);
+
+        // safety: accessing the cells is OK because we have acquired a critical section.
+        unsafe {
+            self.cs_restore.get().write(restore);
+            let (encoder, buffer, sink) = self.parts();
+            encoder.start_frame(|b| buffer.write(b, sink));
+        }
+    }
+
+    /// Implementation of [`Logger::flush`](crate::Logger::flush).
+    ///
+    /// # Safety
+    ///
+    /// Must only be called between `acquire()` and `release()`.
+    pub unsafe fn flush(&self) {
+        if !self.taken.load(Ordering::Relaxed) {
+            panic!("defmt flush out of context")
+        }
+
+        // safety: accessing the cells is OK because we have acquired a critical section.
+        unsafe {
+            let (_, buffer, sink) = self.parts();
+            buffer.flush(sink);
+            sink.flush();
+        }
+    }
+
+    /// Implementation of [`Logger::release`](crate::Logger::release).
+    ///
+    /// # Safety
+    ///
+    /// Must be called exactly once for each `acquire()`, in the same execution context.
+    pub unsafe fn release(&self) {
+        if !self.taken.load(Ordering::Relaxed) {
+            panic!("defmt release out of context")
+        }
+
+        // safety: accessing the cells is OK because we have acquired a critical section.
+        unsafe {
+            let (encoder, buffer, sink) = self.parts();
+            encoder.end_frame(|b| buffer.write(b, sink));
+            buffer.flush(sink);
+            sink.end_frame();
+
+            let restore = self.cs_restore.get().read();
+            self.taken.store(false, Ordering::Relaxed);
+            // paired with exactly one acquire call
+            critical_section::release(restore);
+        }
+    }
+
+    /// Implementation of [`Logger::write`](crate::Logger::write).
+    ///
+    /// # Safety
+    ///
+    /// Must only be called between `acquire()` and `release()`.
+    pub unsafe fn write(&self, bytes: &[u8]) {
+        if !self.taken.load(Ordering::Relaxed) {
+            panic!("defmt write out of context")
+        }
+
+        // safety: accessing the cells is OK because we have acquired a critical section.
+        unsafe {
+            let (encoder, buffer, sink) = self.parts();
+            encoder.write(bytes, |b| buffer.write(b, sink));
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Must only be called while the logger is acquired.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn parts(&self) -> (&mut Encoder, &mut Buffer<N>, &mut S) {
+        unsafe {
+            (
+                &mut *self.encoder.get(),
+                &mut *self.buffer.get(),
+                &mut *self.sink.get(),
+            )
+        }
+    }
+}
+
+/// Collects encoded data, so that the sink gets it in as few calls as possible.
+struct Buffer<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Buffer<N> {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Append `bytes`, writing out the buffer whenever it is full.
+    fn write(&mut self, mut bytes: &[u8], sink: &mut impl Sink) {
+        while !bytes.is_empty() {
+            if self.len == N {
+                self.flush(sink);
+            }
+            let n = bytes.len().min(N - self.len);
+            self.bytes[self.len..][..n].copy_from_slice(&bytes[..n]);
+            self.len += n;
+            bytes = &bytes[n..];
+        }
+    }
+
+    /// Write out and clear the buffer.
+    fn flush(&mut self, sink: &mut impl Sink) {
+        if self.len != 0 {
+            sink.write(&self.bytes[..self.len]);
+            self.len = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct VecSink {
+        writes: Vec<Vec<u8>>,
+        /// Number of writes when each frame ended
+        frame_ends: Vec<usize>,
+        flushes: usize,
+    }
+
+    impl Sink for VecSink {
+        fn write(&mut self, bytes: &[u8]) {
+            self.writes.push(bytes.to_vec());
+        }
+
+        fn end_frame(&mut self) {
+            self.frame_ends.push(self.writes.len());
+        }
+
+        fn flush(&mut self) {
+            self.flushes += 1;
+        }
+    }
+
+    /// Encodes `frames` in order, like a fresh logger does.
+    fn encode(frames: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut encoder = Encoder::new();
+        frames
+            .iter()
+            .map(|frame| {
+                let mut encoded = vec![];
+                encoder.start_frame(|b| encoded.extend_from_slice(b));
+                encoder.write(frame, |b| encoded.extend_from_slice(b));
+                encoder.end_frame(|b| encoded.extend_from_slice(b));
+                encoded
+            })
+            .collect()
+    }
+
+    fn log<S: Sink, const N: usize>(logger: &BufferedLogger<S, N>, frame: &[u8]) {
+        logger.acquire();
+        unsafe {
+            for byte in frame {
+                logger.write(&[*byte]);
+            }
+            logger.release();
+        }
+    }
+
+    #[test]
+    fn writes_frame_at_once() {
+        let logger = BufferedLogger::<_, 64>::new(VecSink::default());
+        log(&logger, &[1, 0, 2, 3]);
+        log(&logger, &[4, 5]);
+
+        logger.with_sink(|sink| {
+            assert_eq!(sink.writes, encode(&[&[1, 0, 2, 3], &[4, 5]]));
+            assert_eq!(sink.frame_ends, [1, 2]);
+            assert_eq!(sink.flushes, 0);
+        });
+    }
+
+    #[test]
+    fn writes_when_full() {
+        let logger = BufferedLogger::<_, 4>::new(VecSink::default());
+        let frame = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        log(&logger, &frame);
+
+        logger.with_sink(|sink| {
+            assert!(sink.writes.len() > 1);
+            assert!(sink.writes.iter().all(|write| write.len() <= 4));
+            assert_eq!(sink.writes.concat(), encode(&[&frame]).concat());
+            assert_eq!(sink.frame_ends, [sink.writes.len()]);
+        });
+    }
+
+    #[test]
+    fn flush_drains_buffer() {
+        let logger = BufferedLogger::<_, 64>::new(VecSink::default());
+        logger.acquire();
+        unsafe {
+            logger.write(&[1, 2, 3]);
+            logger.flush();
+            logger.release();
+        }
+
+        logger.with_sink(|sink| {
+            assert_eq!(sink.flushes, 1);
+            assert_eq!(sink.writes.concat(), encode(&[&[1, 2, 3]]).concat());
+        });
+    }
+
+    #[test]
+    fn discards_without_sink() {
+        let logger = BufferedLogger::<Option<VecSink>, 64>::new(None);
+        log(&logger, &[1, 2]);
+        logger.with_sink(|sink| *sink = Some(VecSink::default()));
+        log(&logger, &[3]);
+
+        // the discarded frame was still encoded
+        let encoded = encode(&[&[1, 2], &[3]]);
+        logger.with_sink(|sink| assert_eq!(sink.as_ref().unwrap().writes, encoded[1..]));
+    }
+}
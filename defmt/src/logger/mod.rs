@@ -0,0 +1,121 @@
+//! Building blocks for global loggers.
+//!
+//! Most transports only need a way to write bytes. [`BufferedLogger`] takes care of everything
+//! else a global logger has to do: it takes a critical section for the duration of each log frame,
+//! detects re-entrant use, encodes the frames with [`Encoder`](crate::Encoder) and collects the
+//! encoded data in a buffer that is handed to a [`Sink`] once per frame, or whenever it is full.
+//!
+//! On multi-core devices, [`MultiCoreLogger`] lets every core encode its frames on its own, so the
+//! cores only contend for the sink while a complete frame is written to it.
+//!
+//! This module requires the `logger` Cargo feature, and a [`critical-section`] implementation
+//! suitable for the target.
+//!
+//! [`critical-section`]: https://crates.io/crates/critical-section
+//!
+//! # Example
+//!
+//! ```
+//! use defmt::logger::{BufferedLogger, Sink};
+//!
+//! struct Uart {
+//!     // ...
+//! }
+//!
+//! impl Sink for Uart {
+//!     fn write(&mut self, bytes: &[u8]) {
+//!         // blocking write of `bytes` to the UART
+//! # let _ = bytes;
+//!     }
+//! }
+//!
+//! // `None` until the UART has been initialized; frames logged before that are discarded.
+//! static LOGGER: BufferedLogger<Option<Uart>, 64> = BufferedLogger::new(None);
+//! defmt::buffered_logger!(LOGGER);
+//!
+//! fn init() {
+//!     LOGGER.with_sink(|sink| *sink = Some(Uart {}));
+//! }
+//! ```
+
+mod buffered;
+mod multi_core;
+
+pub use self::{buffered::BufferedLogger, multi_core::MultiCoreLogger};
+
+/// The destination of the encoded log data of a [`BufferedLogger`] or [`MultiCoreLogger`].
+pub trait Sink {
+    /// Writes out encoded log data.
+    ///
+    /// The write operation must not fail. This is a "best effort" operation, I/O errors should be
+    /// discarded.
+    ///
+    /// This is called from within a critical section.
+    fn write(&mut self, bytes: &[u8]);
+
+    /// Called after the last data of each frame has been written, e.g. to hand the complete frame
+    /// on. The default implementation does nothing.
+    ///
+    /// This is called from within a critical section.
+    fn end_frame(&mut self) {}
+
+    /// Blocks until all data written so far has been transmitted.
+    ///
+    /// This is called by [`defmt::flush`](crate::flush), after the buffered data has been
+    /// written. The default implementation does nothing.
+    fn flush(&mut self) {}
+}
+
+/// Discards all data while `None`.
+impl<S: Sink> Sink for Option<S> {
+    fn write(&mut self, bytes: &[u8]) {
+        if let Some(sink) = self {
+            sink.write(bytes)
+        }
+    }
+
+    fn end_frame(&mut self) {
+        if let Some(sink) = self {
+            sink.end_frame()
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(sink) = self {
+            sink.flush()
+        }
+    }
+}
+
+/// Installs a [`BufferedLogger`](crate::logger::BufferedLogger) or
+/// [`MultiCoreLogger`](crate::logger::MultiCoreLogger) static as the global logger.
+///
+/// This defines a `#[global_logger]` that forwards to the given static, so it must only be used
+/// once across the crate graph. See the [`logger`](crate::logger) module for an example.
+#[macro_export]
+macro_rules! buffered_logger {
+    ($logger:path) => {
+        const _: () = {
+            #[$crate::global_logger]
+            struct Logger;
+
+            unsafe impl $crate::Logger for Logger {
+                fn acquire() {
+                    $logger.acquire()
+                }
+
+                unsafe fn flush() {
+                    unsafe { $logger.flush() }
+                }
+
+                unsafe fn release() {
+                    unsafe { $logger.release() }
+                }
+
+                unsafe fn write(bytes: &[u8]) {
+                    unsafe { $logger.write(bytes) }
+                }
+            }
+        };
+    };
+}
@@ -0,0 +1,341 @@
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{compiler_fence, AtomicU32, AtomicUsize, Ordering},
+};
+
+use super::Sink;
+use crate::Encoder;
+
+/// A global logger for multi-core devices that encodes the frames of each of its `CORES` cores
+/// separately, into a buffer of `N` bytes per core, and writes complete frames to a [`Sink`].
+///
+/// Unlike [`BufferedLogger`](super::BufferedLogger), this doesn't hold a critical section while a
+/// frame is being encoded, so a core that logs doesn't stall the others. A critical section is
+/// only taken to write a complete frame to the sink, which keeps the frames of different cores from
+/// interleaving.
+///
+/// `core_id` returns the index of the core it is called on, which must be less than `CORES`. To
+/// let the printer tell the cores apart, also tag the frames with [`core_id!`](crate::core_id).
+///
+/// Each core encodes one frame at a time. A frame is dropped if it doesn't fit into the buffer, or
+/// if it is logged while another frame is being logged on the same core, e.g. by an interrupt
+/// handler; [`dropped_frames`](Self::dropped_frames) counts them.
+///
+/// Install it as the global logger with [`buffered_logger!`](crate::buffered_logger).
+///
+/// # Example
+///
+/// ```
+/// use defmt::logger::{MultiCoreLogger, Sink};
+///
+/// # struct Uart;
+/// # impl Sink for Uart { fn write(&mut self, _: &[u8]) {} }
+/// # fn current_core() -> usize { 0 }
+/// // two cores, with a 128 byte frame buffer each
+/// static LOGGER: MultiCoreLogger<Option<Uart>, 2, 128> =
+///     MultiCoreLogger::new(current_core, None);
+/// defmt::buffered_logger!(LOGGER);
+/// defmt::core_id!(current_core() as u8);
+/// ```
+pub struct MultiCoreLogger<S, const CORES: usize, const N: usize> {
+    core_id: fn() -> usize,
+    cores: [Core<N>; CORES],
+    /// Number of dropped frames; only modified within a critical section
+    dropped: AtomicU32,
+    sink: UnsafeCell<S>,
+}
+
+// SAFETY: the cell of each core is only accessed from that core, while it isn't accessed from a
+// nested context on the same core; the sink is only accessed within a critical section.
+unsafe impl<S: Send, const CORES: usize, const N: usize> Sync for MultiCoreLogger<S, CORES, N> {}
+
+impl<S: Sink, const CORES: usize, const N: usize> MultiCoreLogger<S, CORES, N> {
+    /// Creates a logger that writes to `sink`, and calls `core_id` to find out which core logs.
+    pub const fn new(core_id: fn() -> usize, sink: S) -> Self {
+        assert!(CORES > 0, "there must be at least one core");
+        assert!(N > 0, "the buffer must not be empty");
+
+        Self {
+            core_id,
+            cores: [Core::NEW; CORES],
+            dropped: AtomicU32::new(0),
+            sink: UnsafeCell::new(sink),
+        }
+    }
+
+    /// Gives exclusive access to the sink, e.g. to set it up after the logger has been created.
+    pub fn with_sink<R>(&self, f: impl FnOnce(&mut S) -> R) -> R {
+        // safety: accessing the cell is OK because we are in a critical section.
+        critical_section::with(|_| f(unsafe { &mut *self.sink.get() }))
+    }
+
+    /// Returns the number of frames that were dropped, on all cores.
+    ///
+    /// The counter wraps around on overflow.
+    pub fn dropped_frames(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Implementation of [`Logger::acquire`](crate::Logger::acquire).
+    pub fn acquire(&self) {
+        let core = self.core();
+        // nested contexts on this core restore `depth` before we continue, so there is no need
+        // for a CAS, which some targets don't have
+        let depth = core.depth.load(Ordering::Relaxed);
+        core.depth.store(depth + 1, Ordering::Relaxed);
+        // keep accesses to the cells from being moved before `depth` is updated
+        compiler_fence(Ordering::SeqCst);
+
+        if depth == 0 {
+            // safety: accessing the cells is OK because this is the outermost frame on this core.
+            unsafe {
+                let (encoder, frame) = core.parts();
+                encoder.start_frame(|b| frame.push(b));
+            }
+        }
+    }
+
+    /// Implementation of [`Logger::flush`](crate::Logger::flush).
+    ///
+    /// Only the frames that have been completed are flushed.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called between `acquire()` and `release()`.
+    pub unsafe fn flush(&self) {
+        match self.core().depth.load(Ordering::Relaxed) {
+            0 => panic!("defmt flush out of context"),
+            // safety: accessing the cell is OK because we are in a critical section.
+            1 => critical_section::with(|_| unsafe { (*self.sink.get()).flush() }),
+            _ => {}
+        }
+    }
+
+    /// Implementation of [`Logger::release`](crate::Logger::release).
+    ///
+    /// # Safety
+    ///
+    /// Must be called exactly once for each `acquire()`, in the same execution context.
+    pub unsafe fn release(&self) {
+        let core = self.core();
+        let depth = core.depth.load(Ordering::Relaxed);
+        match depth {
+            0 => panic!("defmt release out of context"),
+            // safety: accessing the cells is OK because this is the outermost frame on this core,
+            // and the sink is only accessed within a critical section.
+            1 => unsafe {
+                let (encoder, frame) = core.parts();
+                encoder.end_frame(|b| frame.push(b));
+                critical_section::with(|_| {
+                    if frame.overflowed {
+                        self.count_dropped();
+                    } else {
+                        let sink = &mut *self.sink.get();
+                        sink.write(&frame.bytes[..frame.len]);
+                        sink.end_frame();
+                    }
+                });
+                frame.clear();
+            },
+            _ => critical_section::with(|_| self.count_dropped()),
+        }
+
+        compiler_fence(Ordering::SeqCst);
+        core.depth.store(depth - 1, Ordering::Relaxed);
+    }
+
+    /// Implementation of [`Logger::write`](crate::Logger::write).
+    ///
+    /// # Safety
+    ///
+    /// Must only be called between `acquire()` and `release()`.
+    pub unsafe fn write(&self, bytes: &[u8]) {
+        let core = self.core();
+        match core.depth.load(Ordering::Relaxed) {
+            0 => panic!("defmt write out of context"),
+            // safety: accessing the cells is OK because this is the outermost frame on this core.
+            1 => unsafe {
+                let (encoder, frame) = core.parts();
+                encoder.write(bytes, |b| frame.push(b));
+            },
+            _ => {}
+        }
+    }
+
+    fn core(&self) -> &Core<N> {
+        &self.cores[(self.core_id)()]
+    }
+
+    /// Must only be called within a critical section.
+    fn count_dropped(&self) {
+        // no need for `fetch_add` because we are in a critical section
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        self.dropped
+            .store(dropped.wrapping_add(1), Ordering::Relaxed);
+    }
+}
+
+/// The state of one core.
+struct Core<const N: usize> {
+    /// Number of frames being logged on this core; more than 1 if the outermost one was interrupted
+    depth: AtomicUsize,
+    encoder: UnsafeCell<Encoder>,
+    frame: UnsafeCell<Frame<N>>,
+}
+
+impl<const N: usize> Core<N> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: Self = Self {
+        depth: AtomicUsize::new(0),
+        encoder: UnsafeCell::new(Encoder::new()),
+        frame: UnsafeCell::new(Frame::new()),
+    };
+
+    /// # Safety
+    ///
+    /// Must only be called by the outermost frame on this core.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn parts(&self) -> (&mut Encoder, &mut Frame<N>) {
+        unsafe { (&mut *self.encoder.get(), &mut *self.frame.get()) }
+    }
+}
+
+/// An encoded frame, which is only written out once it is complete.
+struct Frame<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+    /// Whether the frame didn't fit and is going to be dropped
+    overflowed: bool,
+}
+
+impl<const N: usize> Frame<N> {
+    const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Appends `bytes`, unless the frame doesn't fit anymore.
+    fn push(&mut self, bytes: &[u8]) {
+        if self.overflowed || bytes.len() > N - self.len {
+            self.overflowed = true;
+            return;
+        }
+        self.bytes[self.len..][..bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+        self.overflowed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    std::thread_local! {
+        static CURRENT_CORE: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn current_core() -> usize {
+        CURRENT_CORE.with(|core| core.get())
+    }
+
+    fn switch_to(core: usize) {
+        CURRENT_CORE.with(|current| current.set(core));
+    }
+
+    #[derive(Default)]
+    struct VecSink {
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl Sink for VecSink {
+        fn write(&mut self, bytes: &[u8]) {
+            self.writes.push(bytes.to_vec());
+        }
+    }
+
+    /// Encodes `frame` like a core's first frame.
+    fn encode(frame: &[u8]) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        let mut encoded = vec![];
+        encoder.start_frame(|b| encoded.extend_from_slice(b));
+        encoder.write(frame, |b| encoded.extend_from_slice(b));
+        encoder.end_frame(|b| encoded.extend_from_slice(b));
+        encoded
+    }
+
+    #[test]
+    fn cores_write_whole_frames() {
+        let logger = MultiCoreLogger::<_, 2, 64>::new(current_core, VecSink::default());
+
+        switch_to(0);
+        logger.acquire();
+        unsafe { logger.write(&[1, 2]) };
+
+        switch_to(1);
+        logger.acquire();
+        unsafe {
+            logger.write(&[3, 4]);
+            logger.release();
+        }
+
+        switch_to(0);
+        unsafe {
+            logger.write(&[5]);
+            logger.release();
+        }
+
+        logger.with_sink(|sink| assert_eq!(sink.writes, [encode(&[3, 4]), encode(&[1, 2, 5])]));
+        assert_eq!(logger.dropped_frames(), 0);
+    }
+
+    #[test]
+    fn drops_nested_frames() {
+        let logger = MultiCoreLogger::<_, 1, 64>::new(|| 0, VecSink::default());
+
+        logger.acquire();
+        unsafe { logger.write(&[1]) };
+        // e.g. an interrupt handler that logs
+        logger.acquire();
+        unsafe {
+            logger.write(&[2]);
+            logger.release();
+            logger.write(&[3]);
+            logger.release();
+        }
+
+        logger.with_sink(|sink| assert_eq!(sink.writes, [encode(&[1, 3])]));
+        assert_eq!(logger.dropped_frames(), 1);
+    }
+
+    #[test]
+    fn drops_frames_that_do_not_fit() {
+        let logger = MultiCoreLogger::<_, 1, 8>::new(|| 0, VecSink::default());
+
+        logger.acquire();
+        unsafe {
+            logger.write(&[1; 10]);
+            logger.release();
+        }
+        assert_eq!(logger.dropped_frames(), 1);
+
+        logger.acquire();
+        unsafe {
+            logger.write(&[2]);
+            logger.release();
+        }
+        logger.with_sink(|sink| {
+            assert_eq!(sink.writes.len(), 1);
+            // the leading frame separator was part of the dropped frame
+            assert_eq!(sink.writes[0], encode(&[2])[1..]);
+        });
+    }
+}
@@ -0,0 +1,27 @@
+use crate::{export, Str};
+
+/// Guard of a span entered with [`span!`]; logs the exit frame of the span when dropped.
+///
+/// The decoder pairs the exit frame with the enter frame to compute how long the span lasted.
+///
+/// [`span!`]: macro.span.html
+#[must_use = "the span is exited as soon as the guard is dropped"]
+pub struct Span {
+    /// Format string of the exit frame, or `None` if the span is filtered out
+    exit: Option<Str>,
+}
+
+impl Span {
+    #[doc(hidden)]
+    pub fn new(exit: Option<Str>) -> Self {
+        Self { exit }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(exit) = &self.exit {
+            export::acquire_header_and_release(exit);
+        }
+    }
+}
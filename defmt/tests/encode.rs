@@ -1011,3 +1011,97 @@ fn core_fmt_adapters() {
     let index = fetch_string_index();
     check_format!(&Display2Format(&123u8), [index, b'1', b'2', b'3', 0xffu8]);
 }
+
+#[test]
+fn log_once() {
+    let index = fetch_string_index();
+    for i in 0..3u8 {
+        defmt::error_once!("once {=u8}", i);
+    }
+    check!([
+        index, // "once {=u8}"
+        0u8,   // only the first iteration is logged
+    ]);
+}
+
+#[test]
+fn log_every() {
+    let index = fetch_string_index();
+    for i in 0..5u8 {
+        defmt::error_every!(2, "every {=u8}", i);
+    }
+    check!([
+        index, // "every {=u8}"
+        0u8,
+        inc(index, 1), // the interner mock returns a new index for each frame
+        2u8,
+        inc(index, 2),
+        4u8,
+    ]);
+}
+
+#[test]
+fn log_throttled() {
+    let index = fetch_string_index();
+    for now in [5, 8, 15, 20, 25u32] {
+        defmt::export::set_throttle_clock(now);
+        defmt::error_throttled!(10, "throttled {=u32}", now);
+    }
+    check!([
+        index, // "throttled {=u32}"
+        5u32,
+        inc(index, 1),
+        15u32,
+        inc(index, 2),
+        25u32,
+    ]);
+}
+
+#[test]
+fn log_fields() {
+    let index = fetch_string_index();
+    let rssi = -52i8;
+    defmt::error!("connected {=u8}", 1, peer = 42u8, rssi = rssi);
+    check!([
+        index,         // "connected {=u8}{@peer}{@rssi}"
+        1u8,           // message parameter
+        inc(index, 1), // "{=u8}" / impl Format for u8
+        42u8,          // peer
+        inc(index, 2), // "{=i8}" / impl Format for i8
+        rssi,          // rssi
+    ]);
+}
+
+#[test]
+fn log_fields_after_explicit_indices() {
+    let index = fetch_string_index();
+    defmt::error!("{0=u8} {0=u8}", 7, peer = 42u8);
+    check!([
+        index,         // "{0=u8} {0=u8}{@peer}"
+        7u8,           // message parameter, formatted twice
+        inc(index, 1), // "{=u8}" / impl Format for u8
+        42u8,          // peer
+    ]);
+}
+
+#[test]
+fn span_filtered_out() {
+    // `DEFMT_LOG` is unset, so spans, like `info!`, are filtered out
+    let index = fetch_string_index();
+    {
+        let _span = defmt::span!("flash_erase", sector = 3u32);
+    }
+    assert_eq!(fetch_string_index(), index);
+    assert!(defmt::export::fetch_bytes().is_empty());
+}
+
+#[test]
+fn span_guard() {
+    let index = fetch_string_index();
+    let span = defmt::Span::new(Some(defmt::intern!("flash_erase")));
+    assert!(defmt::export::fetch_bytes().is_empty());
+    drop(span);
+    check!([
+        index, // "flash_erase"
+    ]);
+}
@@ -0,0 +1,42 @@
+#![cfg(all(feature = "unstable-test", feature = "last-frame"))]
+
+// NOTE the kept frame is global, so all checks are in one test to keep them from running in
+// parallel
+
+use defmt::{export::fetch_string_index, last_frame};
+
+#[test]
+fn keeps_the_frame_of_the_panic_message() {
+    let mut buf = [0; 16];
+
+    let index = fetch_string_index();
+    defmt::println!("not a panic: {=u8}", 1);
+    assert!(!last_frame::is_panic());
+    let len = last_frame::copy(&mut buf).unwrap();
+    assert_eq!(
+        buf[..len],
+        [index.to_le_bytes()[0], index.to_le_bytes()[1], 1]
+    );
+
+    let index = fetch_string_index();
+    let panic = std::panic::catch_unwind(|| defmt::panic!("answer: {=u8}", 42));
+    assert!(panic.is_err());
+    assert!(last_frame::is_panic());
+    let len = last_frame::copy(&mut buf).unwrap();
+    assert_eq!(
+        buf[..len],
+        [index.to_le_bytes()[0], index.to_le_bytes()[1], 42]
+    );
+
+    // logging it again keeps it as the last frame, no longer marked as panic message
+    defmt::export::fetch_bytes();
+    last_frame::log(&buf[..len]);
+    assert_eq!(defmt::export::fetch_bytes(), buf[..len]);
+    assert!(!last_frame::is_panic());
+    assert_eq!(last_frame::copy(&mut buf), Some(len));
+
+    // frames that don't fit into the buffer aren't kept
+    defmt::println!("too long: {=[u8]}", [0; 128]);
+    assert_eq!(last_frame::copy(&mut buf), None);
+    assert_eq!(last_frame::copy(&mut [0; 256]), None);
+}
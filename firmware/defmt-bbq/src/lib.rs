@@ -0,0 +1,176 @@
+//! [`defmt`](https://github.com/knurling-rs/defmt) global logger that buffers log frames in RAM.
+//!
+//! Log frames are encoded into an in-RAM ring buffer, so logging never waits for a transport.
+//! Moving the data out is left to the application: it calls [`drain`] (or [`read`]) from wherever
+//! it suits, for example an idle task or a DMA completion handler, and writes the data to a UART,
+//! USB or any other transport that `defmt-print` or another decoder can read.
+//!
+//! ```no_run
+//! # struct Uart;
+//! # impl Uart { fn write(&mut self, _: &[u8]) {} }
+//! # let mut uart = Uart;
+//! use defmt_bbq as _;
+//!
+//! // in the idle loop
+//! loop {
+//!     defmt_bbq::drain(|chunk| uart.write(chunk));
+//! }
+//! ```
+//!
+//! A frame is only made available to [`drain`] once it is complete. Frames that don't fit into
+//! the free space of the buffer are dropped as a whole, so the stream stays decodable, and counted
+//! by [`dropped_frames`].
+//!
+//! The buffer size (default: 1024 bytes) can be configured with the `DEFMT_BBQ_BUFFER_SIZE`
+//! environment variable; it must be a power of 2.
+//!
+//! # Critical section implementation
+//!
+//! Logging is not lock-free. The logger is a [`defmt::logger::BufferedLogger`], which holds a
+//! [`critical-section`](https://github.com/rust-embedded/critical-section) while a frame is
+//! encoded into RAM, so that frames logged from different contexts don't interleave. Encoding a
+//! frame doesn't wait for a transport, but interrupts are masked for the time it takes to encode
+//! each frame, which grows with the size of the frame.
+//!
+//! Only the consumer side is lock-free: the ring buffer is a single-producer, single-consumer
+//! queue, so [`drain`] and [`read`] only take the critical section briefly to check that they
+//! aren't run reentrantly, and can be interrupted by logging at any point.
+//!
+//! You must import a crate that provides a `critical-section` implementation suitable for the
+//! current target. See the `critical-section` README for details.
+//!
+//! For example, for single-core privileged-mode Cortex-M targets, you can add
+//! the following to your Cargo.toml.
+//!
+//! ```toml
+//! [dependencies]
+//! cortex-m = { version = "0.7.6", features = ["critical-section-single-core"]}
+//! ```
+
+#![no_std]
+
+mod consts;
+mod ring;
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use defmt::logger::{BufferedLogger, Sink};
+
+use crate::{
+    consts::BUF_SIZE,
+    ring::{Producer, Ring},
+};
+
+/// How many encoded bytes the logger collects before it writes them to [`RING`]
+const CHUNK_SIZE: usize = 16;
+
+/// The defmt global logger
+static LOGGER: BufferedLogger<RingSink, CHUNK_SIZE> = BufferedLogger::new(RingSink {
+    producer: Producer::new(),
+});
+defmt::buffered_logger!(LOGGER);
+
+/// The buffered, encoded log frames
+static RING: Ring<BUF_SIZE> = Ring::new();
+
+/// Number of frames dropped because they didn't fit into the buffer; only modified by the logger
+static DROPPED_FRAMES: AtomicU32 = AtomicU32::new(0);
+
+/// Is `true` while the buffer is being drained
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Moves all buffered log data out of the buffer.
+///
+/// `write` is called with each contiguous chunk of buffered data, up to two times. It must consume
+/// the whole chunk. Returns the number of bytes drained.
+///
+/// Logging from within `write`, or from an interrupt while `drain` runs, is fine. Frames logged
+/// in the meantime are left for the next call.
+///
+/// Panics if called while another `drain` or [`read`] is running.
+pub fn drain(mut write: impl FnMut(&[u8])) -> usize {
+    consume(|chunk| {
+        write(chunk);
+        chunk.len()
+    })
+}
+
+/// Copies buffered log data into `buf`, and removes it from the buffer.
+///
+/// Returns the number of bytes copied. Use this instead of [`drain`] when the data has to be
+/// moved somewhere the buffer can't be borrowed from, e.g. into a DMA buffer.
+///
+/// Panics if called while another [`drain`] or `read` is running.
+pub fn read(buf: &mut [u8]) -> usize {
+    let mut len = 0;
+    consume(|chunk| {
+        let n = chunk.len().min(buf.len() - len);
+        buf[len..][..n].copy_from_slice(&chunk[..n]);
+        len += n;
+        n
+    })
+}
+
+/// Returns the number of frames dropped because the buffer was full.
+///
+/// The counter wraps around on overflow.
+pub fn dropped_frames() -> u32 {
+    DROPPED_FRAMES.load(Ordering::Relaxed)
+}
+
+/// Hands out the readable chunks of the buffer to `f`, which returns how much it consumed.
+fn consume(mut f: impl FnMut(&[u8]) -> usize) -> usize {
+    critical_section::with(|_| {
+        if DRAINING.load(Ordering::Relaxed) {
+            panic!("defmt-bbq drained reentrantly")
+        }
+        DRAINING.store(true, Ordering::Relaxed);
+    });
+
+    let mut total = 0;
+    // the data may wrap around the end of the buffer; the second chunk is its start
+    for _ in 0..2 {
+        // safety: `DRAINING` makes sure we are the only consumer.
+        unsafe {
+            let chunk = RING.readable();
+            if chunk.is_empty() {
+                break;
+            }
+            let n = f(chunk);
+            RING.consume(n);
+            total += n;
+            if n < chunk.len() {
+                break;
+            }
+        }
+    }
+
+    DRAINING.store(false, Ordering::Relaxed);
+    total
+}
+
+/// Writes the encoded frames into [`RING`]; only accessed while the logger is acquired.
+struct RingSink {
+    /// The frame being written to the buffer
+    producer: Producer,
+}
+
+impl Sink for RingSink {
+    fn write(&mut self, bytes: &[u8]) {
+        // safety: the logger holds a critical section while it writes to the sink, so we are the
+        // only producer.
+        unsafe { RING.push(&mut self.producer, bytes) }
+    }
+
+    fn end_frame(&mut self) {
+        // safety: see `write`
+        if !unsafe { RING.commit(&mut self.producer) } {
+            // no need for `fetch_add` because we are in a critical section
+            let dropped = DROPPED_FRAMES.load(Ordering::Relaxed);
+            DROPPED_FRAMES.store(dropped.wrapping_add(1), Ordering::Relaxed);
+        }
+    }
+
+    // `flush` does nothing: only `drain` and `read` move data out of the buffer, and waiting for
+    // them here, with the logger acquired, could block forever.
+}
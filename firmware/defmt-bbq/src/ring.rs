@@ -0,0 +1,190 @@
+use core::{
+    cell::UnsafeCell,
+    ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A single-producer, single-consumer ring buffer of bytes that only hands complete frames to the
+/// consumer.
+///
+/// `read` and `write` count the bytes ever consumed and committed; they wrap around, which is why
+/// `N` must be a power of 2.
+pub(crate) struct Ring<const N: usize> {
+    buffer: UnsafeCell<[u8; N]>,
+    /// Bytes consumed; only modified by the consumer
+    read: AtomicUsize,
+    /// Bytes of complete frames; only modified by the producer
+    write: AtomicUsize,
+}
+
+/// State of the frame that is currently being produced.
+pub(crate) struct Producer {
+    /// Where the next byte of the frame goes
+    cursor: usize,
+    /// Whether the frame didn't fit and is going to be dropped
+    overflowed: bool,
+}
+
+impl Producer {
+    pub(crate) const fn new() -> Self {
+        Self {
+            cursor: 0,
+            overflowed: false,
+        }
+    }
+}
+
+impl<const N: usize> Ring<N> {
+    pub(crate) const fn new() -> Self {
+        assert!(N.is_power_of_two());
+
+        Self {
+            buffer: UnsafeCell::new([0; N]),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+        }
+    }
+
+    /// Appends `bytes` to the current frame, unless it doesn't fit anymore.
+    ///
+    /// # Safety
+    ///
+    /// There must only be one producer at a time.
+    pub(crate) unsafe fn push(&self, producer: &mut Producer, bytes: &[u8]) {
+        if producer.overflowed {
+            return;
+        }
+
+        let read = self.read.load(Ordering::Acquire);
+        let free = N - producer.cursor.wrapping_sub(read);
+        if bytes.len() > free {
+            producer.overflowed = true;
+            return;
+        }
+
+        let start = producer.cursor % N;
+        let first = bytes.len().min(N - start);
+        // safety: the bytes written to are neither committed nor unread, so the consumer doesn't
+        // access them.
+        unsafe {
+            let buffer = self.buffer.get().cast::<u8>();
+            ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.add(start), first);
+            ptr::copy_nonoverlapping(bytes[first..].as_ptr(), buffer, bytes.len() - first);
+        }
+        producer.cursor = producer.cursor.wrapping_add(bytes.len());
+    }
+
+    /// Makes the current frame available to the consumer, or discards it if it didn't fit.
+    ///
+    /// Returns `false` if the frame was discarded.
+    ///
+    /// # Safety
+    ///
+    /// There must only be one producer at a time.
+    pub(crate) unsafe fn commit(&self, producer: &mut Producer) -> bool {
+        if producer.overflowed {
+            producer.overflowed = false;
+            producer.cursor = self.write.load(Ordering::Relaxed);
+            false
+        } else {
+            self.write.store(producer.cursor, Ordering::Release);
+            true
+        }
+    }
+
+    /// Returns the longest contiguous run of committed bytes that haven't been consumed yet.
+    ///
+    /// # Safety
+    ///
+    /// There must only be one consumer at a time, and the returned slice must not be used after
+    /// calling [`consume`](Self::consume).
+    pub(crate) unsafe fn readable(&self) -> &[u8] {
+        let write = self.write.load(Ordering::Acquire);
+        let read = self.read.load(Ordering::Relaxed);
+        let start = read % N;
+        let len = write.wrapping_sub(read).min(N - start);
+        // safety: the producer doesn't write to committed bytes that haven't been consumed.
+        unsafe { core::slice::from_raw_parts(self.buffer.get().cast::<u8>().add(start), len) }
+    }
+
+    /// Marks `n` bytes returned by [`readable`](Self::readable) as consumed.
+    ///
+    /// # Safety
+    ///
+    /// There must only be one consumer at a time.
+    pub(crate) unsafe fn consume(&self, n: usize) {
+        let read = self.read.load(Ordering::Relaxed);
+        self.read.store(read.wrapping_add(n), Ordering::Release);
+    }
+}
+
+// safety: the producer and the consumer only access disjoint parts of the buffer, see above.
+unsafe impl<const N: usize> Sync for Ring<N> {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::{vec, vec::Vec};
+
+    use super::*;
+
+    fn produce<const N: usize>(ring: &Ring<N>, producer: &mut Producer, frame: &[u8]) -> bool {
+        unsafe {
+            for byte in frame {
+                ring.push(producer, &[*byte]);
+            }
+            ring.commit(producer)
+        }
+    }
+
+    fn consume_all<const N: usize>(ring: &Ring<N>) -> Vec<u8> {
+        let mut data = vec![];
+        unsafe {
+            loop {
+                let chunk = ring.readable();
+                if chunk.is_empty() {
+                    break data;
+                }
+                data.extend_from_slice(chunk);
+                let n = chunk.len();
+                ring.consume(n);
+            }
+        }
+    }
+
+    #[test]
+    fn only_complete_frames_are_readable() {
+        let ring = Ring::<8>::new();
+        let mut producer = Producer::new();
+
+        unsafe { ring.push(&mut producer, &[1, 2]) };
+        assert_eq!(consume_all(&ring), []);
+
+        unsafe { ring.commit(&mut producer) };
+        assert_eq!(consume_all(&ring), [1, 2]);
+    }
+
+    #[test]
+    fn drops_frames_that_do_not_fit() {
+        let ring = Ring::<8>::new();
+        let mut producer = Producer::new();
+
+        assert!(produce(&ring, &mut producer, &[1, 2, 3, 4, 5]));
+        assert!(!produce(&ring, &mut producer, &[6, 7, 8, 9]));
+        assert!(produce(&ring, &mut producer, &[10, 11, 12]));
+        assert_eq!(consume_all(&ring), [1, 2, 3, 4, 5, 10, 11, 12]);
+    }
+
+    #[test]
+    fn wraps_around() {
+        let ring = Ring::<8>::new();
+        let mut producer = Producer::new();
+
+        for i in 0..10 {
+            let frame = [i, i + 1, i + 2];
+            assert!(produce(&ring, &mut producer, &frame));
+            assert_eq!(consume_all(&ring), frame);
+        }
+    }
+}
@@ -10,11 +10,19 @@
 //!
 //! defmt::info!("Hello");
 //! ```
+//!
+//! By default, the logs are sent over stimulus port 0. If that port is used by other tooling, use
+//! `enable_with_port` to select another one:
+//!
+//! ``` no_run
+//! let p = cortex_m::Peripherals::take().unwrap();
+//! defmt_itm::enable_with_port(p.ITM, 1);
+//! ```
 
 #![doc(html_logo_url = "https://knurling.ferrous-systems.com/knurling_logo_light_text.svg")]
 #![no_std]
 
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use cortex_m::{
     asm, itm,
@@ -27,13 +35,34 @@ compile_error!(
 );
 
 static ENABLED: AtomicBool = AtomicBool::new(false);
+static PORT: AtomicU8 = AtomicU8::new(0);
+
+/// Number of ITM stimulus ports.
+const NUM_PORTS: u8 = 32;
 
 /// Enables defmt logging over the ITM stimulus port 0.
 ///
 /// This needs to be called by the application before defmt logging is used, otherwise the logs will be disposed.
 pub fn enable(itm: ITM) {
-    // enable stimulus port 0
-    unsafe { itm.ter[0].write(1) }
+    enable_with_port(itm, 0)
+}
+
+/// Enables defmt logging over the given ITM stimulus port.
+///
+/// Other stimulus ports stay enabled, so they can still be used by other tooling. On the host,
+/// select the same port when demultiplexing the SWO stream.
+///
+/// This needs to be called by the application before defmt logging is used, otherwise the logs will be disposed.
+///
+/// # Panics
+///
+/// Panics if `port` is not a valid stimulus port, i.e. not below 32.
+pub fn enable_with_port(itm: ITM, port: u8) {
+    assert!(port < NUM_PORTS, "invalid ITM stimulus port {}", port);
+
+    // enable the stimulus port
+    unsafe { itm.ter[0].modify(|ter| ter | 1 << port) }
+    PORT.store(port, Ordering::Relaxed);
     ENABLED.store(true, Ordering::Relaxed);
 }
 
@@ -69,7 +98,7 @@ unsafe impl defmt::Logger for Logger {
 
     unsafe fn flush() {
         // wait for the queue to be able to accept more data
-        while !stim_0().is_fifo_ready() {}
+        while !stim().is_fifo_ready() {}
 
         // delay "a bit" to drain the queue
         // This is a heuristic and might be too short in reality. Please open an issue if it is!
@@ -107,13 +136,13 @@ unsafe impl defmt::Logger for Logger {
 fn do_write(bytes: &[u8]) {
     // NOTE(unsafe) this function will be invoked *after* `enable` has run so this crate now has
     // ownership over the ITM thus it's OK to instantiate the ITM register block here
-    unsafe { itm::write_all(stim_0(), bytes) }
+    unsafe { itm::write_all(stim(), bytes) }
 }
 
-/// Get access to stimulus port 0
+/// Get access to the stimulus port selected in `enable`
 ///
 /// # Safety
 /// Can only be invoked *after* `enable` has run
-unsafe fn stim_0<'a>() -> &'a mut Stim {
-    &mut (*ITM::PTR).stim[0]
+unsafe fn stim<'a>() -> &'a mut Stim {
+    &mut (*ITM::PTR).stim[PORT.load(Ordering::Relaxed) as usize]
 }
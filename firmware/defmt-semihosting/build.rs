@@ -0,0 +1,28 @@
+use std::{env, path::PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=DEFMT_SEMIHOSTING_BUFFER_SIZE");
+
+    let size = env::var("DEFMT_SEMIHOSTING_BUFFER_SIZE")
+        .map(|s| {
+            s.parse()
+                .expect("could not parse DEFMT_SEMIHOSTING_BUFFER_SIZE as usize")
+        })
+        .unwrap_or(256_usize);
+    assert!(size > 0, "DEFMT_SEMIHOSTING_BUFFER_SIZE must not be 0");
+
+    let out_dir_path = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    let out_file_path = out_dir_path.join("consts.rs");
+
+    std::fs::write(
+        out_file_path,
+        format!(
+            "/// Write buffer size (default: 256).
+            ///
+            /// Can be customized by setting the `DEFMT_SEMIHOSTING_BUFFER_SIZE` environment variable.
+            pub(crate) const BUF_SIZE: usize = {};",
+            size
+        ),
+    )
+    .unwrap();
+}
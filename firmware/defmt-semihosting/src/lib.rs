@@ -18,143 +18,44 @@
 //! [dependencies]
 //! cortex-m = { version = "0.7.6", features = ["critical-section-single-core"]}
 //! ```
+//!
+//! # Buffering
+//!
+//! Every semihosting call traps into the debugger, which is slow. To make each log frame cost only
+//! one call, encoded data is collected in a buffer that is written out when the frame ends, when
+//! it is full, or when [`defmt::flush`] is called. The buffer size (default: 256 bytes) can be
+//! configured with the `DEFMT_SEMIHOSTING_BUFFER_SIZE` environment variable.
 
 #![no_std]
 
-use core::{
-    cell::UnsafeCell,
-    sync::atomic::{AtomicBool, Ordering},
-};
-
+use defmt::logger::{BufferedLogger, Sink};
 use semihosting::io::{Stdout, Write as _};
 
-#[defmt::global_logger]
-struct Logger;
-
-static SEMIHOSTING_ENCODER: SemihostingEncoder = SemihostingEncoder::new();
-
-struct SemihostingEncoder {
-    /// A boolean lock
-    ///
-    /// Is `true` when `acquire` has been called and we have exclusive access to
-    /// the rest of this structure.
-    taken: AtomicBool,
-    /// We need to remember this to exit a critical section
-    cs_restore: UnsafeCell<critical_section::RestoreState>,
-    /// A defmt::Encoder for encoding frames
-    encoder: UnsafeCell<defmt::Encoder>,
-    /// A semihosting handle for outputting encoded data
-    handle: UnsafeCell<Option<Stdout>>,
-}
-
-impl SemihostingEncoder {
-    /// Create a new semihosting-based defmt-encoder
-    const fn new() -> SemihostingEncoder {
-        SemihostingEncoder {
-            taken: AtomicBool::new(false),
-            cs_restore: UnsafeCell::new(critical_section::RestoreState::invalid()),
-            encoder: UnsafeCell::new(defmt::Encoder::new()),
-            handle: UnsafeCell::new(None),
-        }
-    }
-
-    /// Acquire the defmt encoder.
-    fn acquire(&self) {
-        // safety: Must be paired with corresponding call to release(), see below
-        let restore = unsafe { critical_section::acquire() };
-
-        // NB: You can re-enter critical sections but we need to make sure
-        // no-one does that.
-        if self.taken.load(Ordering::Relaxed) {
-            panic!("defmt logger taken reentrantly")
-        }
-
-        // no need for CAS because we are in a critical section
-        self.taken.store(true, Ordering::Relaxed);
-
-        // safety: accessing the cell is OK because we have acquired a critical
-        // section.
-        unsafe {
-            self.cs_restore.get().write(restore);
-            let encoder: &mut defmt::Encoder = &mut *self.encoder.get();
-            let handle: &mut Option<Stdout> = &mut *self.handle.get();
-            if handle.is_none() {
-                *handle = semihosting::io::stdout().ok();
-            }
-            encoder.start_frame(|b| {
-                if let Some(h) = handle {
-                    _ = h.write_all(b);
-                }
-            });
-        }
-    }
+use crate::consts::BUF_SIZE;
 
-    /// Release the defmt encoder.
-    unsafe fn release(&self) {
-        if !self.taken.load(Ordering::Relaxed) {
-            panic!("defmt release out of context")
-        }
+mod consts;
 
-        // safety: accessing the cell is OK because we have acquired a critical
-        // section.
-        unsafe {
-            let encoder: &mut defmt::Encoder = &mut *self.encoder.get();
-            let handle: &mut Option<Stdout> = &mut *self.handle.get();
-            encoder.end_frame(|b| {
-                if let Some(h) = handle {
-                    _ = h.write_all(b);
-                }
-            });
-            let restore = self.cs_restore.get().read();
-            self.taken.store(false, Ordering::Relaxed);
-            // paired with exactly one acquire call
-            critical_section::release(restore);
-        }
-    }
+static LOGGER: BufferedLogger<SemihostingSink, BUF_SIZE> =
+    BufferedLogger::new(SemihostingSink { handle: None });
 
-    /// Write bytes to the defmt encoder.
-    unsafe fn write(&self, bytes: &[u8]) {
-        if !self.taken.load(Ordering::Relaxed) {
-            panic!("defmt write out of context")
-        }
+defmt::buffered_logger!(LOGGER);
 
-        // safety: accessing the cell is OK because we have acquired a critical
-        // section.
-        unsafe {
-            let encoder: &mut defmt::Encoder = &mut *self.encoder.get();
-            let handle: &mut Option<Stdout> = &mut *self.handle.get();
-            encoder.write(bytes, |b| {
-                if let Some(h) = handle {
-                    _ = h.write_all(b);
-                }
-            });
-        }
-    }
+/// Writes encoded data to the semihosting stdout.
+struct SemihostingSink {
+    /// Opened on first use
+    handle: Option<Stdout>,
 }
 
-unsafe impl Sync for SemihostingEncoder {}
-
-unsafe impl defmt::Logger for Logger {
-    fn acquire() {
-        SEMIHOSTING_ENCODER.acquire();
-    }
-
-    unsafe fn flush() {
-        // Do nothing.
-        //
-        // semihosting is fundamentally blocking, and does not have I/O buffers the target can control.
-        // After write returns, the host has the data, so there's nothing left to flush.
-    }
-
-    unsafe fn release() {
-        unsafe {
-            SEMIHOSTING_ENCODER.release();
+impl Sink for SemihostingSink {
+    fn write(&mut self, bytes: &[u8]) {
+        if self.handle.is_none() {
+            self.handle = semihosting::io::stdout().ok();
         }
-    }
-
-    unsafe fn write(bytes: &[u8]) {
-        unsafe {
-            SEMIHOSTING_ENCODER.write(bytes);
+        if let Some(h) = &mut self.handle {
+            _ = h.write_all(bytes);
         }
     }
+
+    // semihosting is fundamentally blocking, so once the logger's buffer has been written out, the
+    // host has the data and there's nothing left to flush.
 }
@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, process::Command};
 
 fn main() {
     // allow all possible configs
@@ -10,6 +10,7 @@ fn main() {
         "armv8m",
         "armv8m_base",
         "armv8m_main",
+        "riscv",
     ];
     for config in possible_configs {
         println!("cargo:rustc-check-cfg=cfg({config})");
@@ -27,10 +28,33 @@ fn main() {
         ["cortex_m", "armv8m", "armv8m_base"].as_slice()
     } else if target.starts_with("thumbv8m.main") {
         ["cortex_m", "armv8m", "armv8m_main"].as_slice()
+    } else if target.starts_with("riscv") {
+        ["riscv"].as_slice()
     } else {
         [].as_slice()
     };
     for config in enabled_configs {
         println!("cargo:rustc-cfg={config}");
     }
+
+    // `PanicInfo::message` is stable since Rust 1.81
+    println!("cargo:rustc-check-cfg=cfg(panic_info_message)");
+    if rustc_minor_version().is_some_and(|minor| minor >= 81) {
+        println!("cargo:rustc-cfg=panic_info_message");
+    }
+}
+
+/// Returns the minor version of the compiler, e.g. 81 for Rust 1.81.
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    // e.g. "rustc 1.81.0 (eeb90cda1 2024-09-04)"
+    version
+        .split_whitespace()
+        .nth(1)?
+        .split('.')
+        .nth(1)?
+        .parse()
+        .ok()
 }
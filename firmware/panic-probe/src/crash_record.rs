@@ -0,0 +1,338 @@
+//! Crash record that survives a soft reset.
+//!
+//! With the `crash-record` feature enabled, the panic handler stores the panic location, message
+//! and a few registers in a `.uninit` RAM section, which the runtime leaves untouched on reset. The
+//! next boot can [`take`] the record and report it, for example through defmt:
+//!
+//! ```no_run
+//! if let Some(record) = panic_probe::crash_record::take() {
+//!     defmt::warn!("previous boot crashed: {}", record);
+//! }
+//! ```
+//!
+//! The `.uninit` section is placed by `cortex-m-rt`'s linker script; on RISC-V, the application's
+//! linker script must place it in RAM that isn't zeroed on reset. Its contents are lost on power
+//! loss, and a record is only reported once a valid one has been written.
+//!
+//! The message is kept as text formatted with `core::fmt`, which firmware built with Rust older
+//! than 1.81 can't get apart from the location.
+//!
+//! `defmt::panic!`, `defmt::unwrap!`, `defmt::assert!` and the like log their message with defmt
+//! and then panic without one, so the text only says "explicit panic" inside defmt. With defmt (the
+//! `print-defmt` feature), the record therefore also keeps the defmt frame of the panic message:
+//! the frame these macros logged, or else the frame that `print-defmt` logs. [`take`] logs this
+//! frame again, with its original level and message but a new timestamp. The frame only contains
+//! the indices of interned strings, so it is only decoded correctly if the firmware hasn't changed
+//! since the panic; see `defmt::last_frame`.
+
+#[cfg(panic_info_message)]
+use core::fmt::{self, Write as _};
+use core::{mem::MaybeUninit, panic::PanicInfo, ptr};
+
+/// Maximum number of bytes kept from the end of the panicking file's path.
+const FILE_LEN: usize = 64;
+/// Maximum number of bytes kept from the start of the panic message.
+const MESSAGE_LEN: usize = 128;
+/// Maximum size of the defmt frame of the panic message, as much as `defmt::last_frame` keeps.
+#[cfg(feature = "defmt")]
+const FRAME_LEN: usize = 128;
+/// Marks a valid record; any other value means there is none.
+const MAGIC: u32 = 0x5052_4f42; // "PROB"
+
+#[link_section = ".uninit.panic_probe.CRASH_RECORD"]
+static mut CRASH_RECORD: MaybeUninit<CrashRecord> = MaybeUninit::uninit();
+
+/// Information about a panic, as stored by the panic handler.
+#[derive(Clone)]
+#[repr(C)]
+pub struct CrashRecord {
+    magic: u32,
+    line: u32,
+    column: u32,
+    file_len: u32,
+    message_len: u32,
+    registers: Registers,
+    file: [u8; FILE_LEN],
+    message: [u8; MESSAGE_LEN],
+    #[cfg(feature = "defmt")]
+    frame_len: u32,
+    #[cfg(feature = "defmt")]
+    frame: [u8; FRAME_LEN],
+}
+
+/// Registers captured in the panic handler.
+#[cfg(cortex_m)]
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Registers {
+    /// Stack pointer.
+    pub sp: u32,
+    /// Link register, the return address into the panic machinery.
+    pub lr: u32,
+    /// Interrupt Program Status Register; the exception number, or 0 in thread mode.
+    pub ipsr: u32,
+}
+
+#[cfg(cortex_m)]
+impl Registers {
+    #[inline(always)]
+    fn capture() -> Self {
+        let (sp, lr, ipsr);
+        unsafe {
+            core::arch::asm!(
+                "mov {sp}, sp",
+                "mov {lr}, lr",
+                "mrs {ipsr}, IPSR",
+                sp = out(reg) sp,
+                lr = out(reg) lr,
+                ipsr = out(reg) ipsr,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+        Self { sp, lr, ipsr }
+    }
+}
+
+/// Registers captured in the panic handler.
+#[cfg(riscv)]
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Registers {
+    /// Stack pointer.
+    pub sp: usize,
+    /// Return address register, the return address into the panic machinery.
+    pub ra: usize,
+    /// Machine cause register, the cause of the last trap; identifies the interrupt or exception
+    /// that was being handled, if any.
+    pub mcause: usize,
+}
+
+#[cfg(riscv)]
+impl Registers {
+    #[inline(always)]
+    fn capture() -> Self {
+        let (sp, ra, mcause);
+        unsafe {
+            core::arch::asm!(
+                "mv {sp}, sp",
+                "mv {ra}, ra",
+                "csrr {mcause}, mcause",
+                sp = out(reg) sp,
+                ra = out(reg) ra,
+                mcause = out(reg) mcause,
+                options(nomem, nostack, preserves_flags),
+            );
+        }
+        Self { sp, ra, mcause }
+    }
+}
+
+impl CrashRecord {
+    /// Path of the file that panicked, possibly truncated at the start.
+    pub fn file(&self) -> &str {
+        as_str(&self.file, self.file_len)
+    }
+
+    /// Line number of the panic location.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Column of the panic location.
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// The panic message, possibly truncated at the end.
+    pub fn message(&self) -> &str {
+        as_str(&self.message, self.message_len)
+    }
+
+    /// Registers at the time of the panic.
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    /// The defmt frame of the panic message, without timestamp; empty if there is none.
+    #[cfg(feature = "defmt")]
+    fn frame(&self) -> &[u8] {
+        &self.frame[..self.frame_len as usize]
+    }
+
+    /// Keeps the last defmt frame, see [`defmt::last_frame`].
+    #[cfg(feature = "defmt")]
+    fn store_last_frame(&mut self) {
+        self.frame_len = defmt::last_frame::copy(&mut self.frame).unwrap_or(0) as u32;
+    }
+
+    fn is_valid(&self) -> bool {
+        #[cfg(feature = "defmt")]
+        if self.frame_len as usize > FRAME_LEN {
+            return false;
+        }
+
+        self.magic == MAGIC
+            && self.file_len as usize <= FILE_LEN
+            && self.message_len as usize <= MESSAGE_LEN
+            && core::str::from_utf8(&self.file[..self.file_len as usize]).is_ok()
+            && core::str::from_utf8(&self.message[..self.message_len as usize]).is_ok()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CrashRecord {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "panicked at {=str}:{=u32}:{=u32}: {=str} ({})",
+            self.file(),
+            self.line,
+            self.column,
+            self.message(),
+            self.registers,
+        )
+    }
+}
+
+#[cfg(all(cortex_m, feature = "defmt"))]
+impl defmt::Format for Registers {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "sp={=u32:#010x}, lr={=u32:#010x}, ipsr={=u32}",
+            self.sp,
+            self.lr,
+            self.ipsr
+        )
+    }
+}
+
+#[cfg(all(riscv, feature = "defmt"))]
+impl defmt::Format for Registers {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "sp={=usize:#x}, ra={=usize:#x}, mcause={=usize:#x}",
+            self.sp,
+            self.ra,
+            self.mcause
+        )
+    }
+}
+
+/// Returns the crash record left by the last panic, if any, and clears it.
+///
+/// If the record contains the defmt frame of the panic message, it is logged again first.
+pub fn take() -> Option<CrashRecord> {
+    // SAFETY: every bit pattern is a valid `CrashRecord`, and the panic handler, the only other
+    // user of `CRASH_RECORD`, never returns.
+    let record = unsafe {
+        let slot = ptr::addr_of_mut!(CRASH_RECORD).cast::<CrashRecord>();
+        let record = ptr::read_volatile(slot);
+        ptr::write_volatile(ptr::addr_of_mut!((*slot).magic), 0);
+        record.is_valid().then_some(record)
+    };
+
+    #[cfg(feature = "defmt")]
+    if let Some(record) = &record {
+        defmt::last_frame::log(record.frame());
+    }
+
+    record
+}
+
+/// Stores the crash record of `info`; called by the panic handler.
+#[inline(always)]
+pub(crate) fn store(info: &PanicInfo) {
+    let registers = Registers::capture();
+
+    let mut record = CrashRecord {
+        magic: MAGIC,
+        line: 0,
+        column: 0,
+        file_len: 0,
+        message_len: 0,
+        registers,
+        file: [0; FILE_LEN],
+        message: [0; MESSAGE_LEN],
+        #[cfg(feature = "defmt")]
+        frame_len: 0,
+        #[cfg(feature = "defmt")]
+        frame: [0; FRAME_LEN],
+    };
+
+    if let Some(location) = info.location() {
+        record.line = location.line();
+        record.column = location.column();
+
+        // keep the end of the path, it identifies the file best
+        let mut file = location.file();
+        while file.len() > FILE_LEN {
+            let mut chars = file.chars();
+            chars.next();
+            file = chars.as_str();
+        }
+        record.file[..file.len()].copy_from_slice(file.as_bytes());
+        record.file_len = file.len() as u32;
+    }
+
+    #[cfg(panic_info_message)]
+    {
+        let mut writer = Writer {
+            buf: &mut record.message,
+            len: 0,
+        };
+        write!(writer, "{}", info.message()).ok();
+        record.message_len = writer.len as u32;
+    }
+
+    // the frame that `defmt::panic!` and the like logged right before they panicked
+    #[cfg(feature = "defmt")]
+    if defmt::last_frame::is_panic() {
+        record.store_last_frame();
+    }
+
+    // SAFETY: the panic handler runs with interrupts disabled and never returns.
+    unsafe { ptr::write_volatile(ptr::addr_of_mut!(CRASH_RECORD).cast(), record) }
+}
+
+/// Stores the frame that `print-defmt` just logged, unless the panic came with a defmt frame of its
+/// own; called by the panic handler after [`store`] and printing the panic message.
+#[cfg(feature = "print-defmt")]
+pub(crate) fn store_printed_frame() {
+    // SAFETY: see `store`
+    unsafe {
+        let slot = ptr::addr_of_mut!(CRASH_RECORD).cast::<CrashRecord>();
+        let mut record = ptr::read_volatile(slot);
+        if record.frame_len == 0 {
+            record.store_last_frame();
+            ptr::write_volatile(slot, record);
+        }
+    }
+}
+
+fn as_str(buf: &[u8], len: u32) -> &str {
+    core::str::from_utf8(&buf[..len as usize]).unwrap_or("")
+}
+
+/// Writes into a fixed buffer, truncating at a character boundary.
+#[cfg(panic_info_message)]
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+#[cfg(panic_info_message)]
+impl fmt::Write for Writer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let end = self.len + c.len_utf8();
+            if end > self.buf.len() {
+                return Err(fmt::Error);
+            }
+            c.encode_utf8(&mut self.buf[self.len..end]);
+            self.len = end;
+        }
+        Ok(())
+    }
+}
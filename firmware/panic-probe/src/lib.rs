@@ -15,13 +15,56 @@
 //!   used to efficiently format the message).
 //!
 //! [defmt]: https://github.com/knurling-rs/defmt/
+//!
+//! # Targets
+//!
+//! `panic-probe` supports Cortex-M and RISC-V targets. On RISC-V, interrupts are disabled in machine
+//! mode, and [`hard_fault`] raises an illegal instruction exception instead of a `HardFault`.
+//!
+//! # Crash Record
+//!
+//! With the `crash-record` feature, the panic location, message and a few registers are also
+//! stored in RAM that survives a soft reset, so the next boot can report the panic. With
+//! `print-defmt`, the defmt frame of the panic message is stored as well, which includes the message
+//! of `defmt::panic!`. See the [`crash_record`] module.
+//!
+//! # Post-Panic Action
+//!
+//! By default, the panic handler ends in [`hard_fault`], which makes `probe-run` print a backtrace.
+//! At most one of the following features selects a different action instead:
+//!
+//! - `action-reset`: Resets the device via `SCB::sys_reset`. If defmt is used, the panic handler
+//!   calls `defmt::flush()` first, so the panic message isn't lost. Only supported on Cortex-M.
+//! - `action-loop`: Spins in an endless loop, e.g. until a watchdog resets the device.
+//! - `action-bkpt`: Halts an attached debugger with a `bkpt` (Cortex-M) or `ebreak` (RISC-V)
+//!   instruction.
+//!
+//! With the `hook` feature, the panic handler also calls a function registered with [`hook!`]
+//! after printing the panic message, for example to put the board into a safe state. The hook may
+//! diverge, which replaces the post-panic action; if defmt is used, the panic message is flushed
+//! before the hook is called.
 
 #![no_std]
 #![cfg(target_os = "none")]
 #![doc(html_logo_url = "https://knurling.ferrous-systems.com/knurling_logo_light_text.svg")]
 
-#[cfg(not(cortex_m))]
-compile_error!("`panic-probe` only supports Cortex-M targets (thumbvN-none-eabi[hf])");
+#[cfg(not(any(cortex_m, riscv)))]
+compile_error!(
+    "`panic-probe` only supports Cortex-M (thumbvN-none-eabi[hf]) and RISC-V (riscvN*-unknown-none-elf) targets"
+);
+
+#[cfg(all(riscv, feature = "action-reset"))]
+compile_error!("`action-reset` is only supported on Cortex-M; use `hook!` to reset RISC-V devices");
+
+#[cfg(any(
+    all(feature = "action-reset", feature = "action-loop"),
+    all(feature = "action-reset", feature = "action-bkpt"),
+    all(feature = "action-loop", feature = "action-bkpt"),
+))]
+compile_error!("only one of the `action-*` features of `panic-probe` can be enabled");
+
+#[cfg(feature = "crash-record")]
+pub mod crash_record;
 
 // Functionality `cfg`d out on platforms with OS/libstd.
 #[cfg(target_os = "none")]
@@ -38,23 +81,103 @@ mod imp {
     #[cfg(not(any(feature = "print-rtt", feature = "print-defmt")))]
     fn print(_: &core::panic::PanicInfo) {}
 
+    #[cfg(feature = "hook")]
+    fn hook(info: &PanicInfo) {
+        extern "Rust" {
+            fn _panic_probe_hook(info: &PanicInfo);
+        }
+
+        // SAFETY: the symbol is defined by `panic_probe::hook!` with this signature.
+        unsafe { _panic_probe_hook(info) }
+    }
+
     #[panic_handler]
     fn panic(info: &PanicInfo) -> ! {
         static PANICKED: AtomicBool = AtomicBool::new(false);
 
+        #[cfg(cortex_m)]
         cortex_m::interrupt::disable();
+        #[cfg(riscv)]
+        riscv::interrupt::disable();
 
         // Guard against infinite recursion, just in case.
         if !PANICKED.load(Ordering::Relaxed) {
             PANICKED.store(true, Ordering::Relaxed);
 
+            #[cfg(feature = "crash-record")]
+            crate::crash_record::store(info);
+
             print(info);
+
+            #[cfg(all(feature = "crash-record", feature = "print-defmt"))]
+            crate::crash_record::store_printed_frame();
+
+            // Make sure the panic message leaves the device before it resets, also if the hook
+            // diverges or resets it.
+            #[cfg(all(feature = "defmt", any(feature = "action-reset", feature = "hook")))]
+            defmt::flush();
+
+            #[cfg(feature = "hook")]
+            hook(info);
+
+            // ... and whatever the hook logged
+            #[cfg(all(feature = "action-reset", feature = "hook", feature = "defmt"))]
+            defmt::flush();
+        }
+
+        #[cfg(all(cortex_m, feature = "action-reset"))]
+        cortex_m::peripheral::SCB::sys_reset();
+
+        #[cfg(feature = "action-loop")]
+        loop {
+            core::sync::atomic::compiler_fence(Ordering::SeqCst);
+        }
+
+        #[cfg(feature = "action-bkpt")]
+        loop {
+            #[cfg(cortex_m)]
+            cortex_m::asm::bkpt();
+            #[cfg(riscv)]
+            unsafe {
+                riscv::asm::ebreak()
+            };
         }
 
+        #[cfg(not(any(
+            feature = "action-reset",
+            feature = "action-loop",
+            feature = "action-bkpt"
+        )))]
         crate::hard_fault();
     }
 }
 
+/// Registers a function that the panic handler calls after printing the panic message.
+///
+/// The function takes a `&core::panic::PanicInfo`. It runs with interrupts disabled, before the
+/// post-panic action, and is only called for the first panic. Requires the `hook` feature.
+///
+/// # Examples
+///
+/// ```
+/// fn safe_state(_info: &core::panic::PanicInfo) {
+///     // e.g. switch off the motor driver
+/// }
+///
+/// panic_probe::hook!(safe_state);
+/// ```
+#[cfg(feature = "hook")]
+#[macro_export]
+macro_rules! hook {
+    ($hook:path) => {
+        #[export_name = "_panic_probe_hook"]
+        fn __panic_probe_hook(info: &core::panic::PanicInfo) {
+            let hook: fn(&core::panic::PanicInfo) = $hook;
+            hook(info)
+        }
+    };
+}
+
 /// Trigger a `HardFault` via `udf` instruction.
 ///
 /// This function may be used to as `defmt::panic_handler` to avoid double prints.
@@ -67,7 +190,7 @@ mod imp {
 ///     panic_probe::hard_fault();
 /// }
 /// ```
-#[cfg(target_os = "none")]
+#[cfg(all(target_os = "none", cortex_m))]
 pub fn hard_fault() -> ! {
     // If `UsageFault` is enabled, we disable that first, since otherwise `udf` will cause that
     // exception instead of `HardFault`.
@@ -86,6 +209,17 @@ pub fn hard_fault() -> ! {
     cortex_m::asm::udf();
 }
 
+/// Trigger an illegal instruction exception via `unimp` instruction, the RISC-V counterpart of a
+/// Cortex-M `HardFault`.
+///
+/// Like on Cortex-M, this function may be used as `defmt::panic_handler`.
+#[cfg(all(target_os = "none", riscv))]
+pub fn hard_fault() -> ! {
+    loop {
+        unsafe { core::arch::asm!("unimp", options(nomem, nostack)) };
+    }
+}
+
 #[cfg(feature = "print-rtt")]
 mod print_rtt {
     use core::panic::PanicInfo;
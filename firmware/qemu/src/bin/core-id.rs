@@ -0,0 +1,28 @@
+#![no_std]
+#![no_main]
+
+use cortex_m as _;
+use cortex_m_rt::entry;
+use semihosting::process::ExitCode;
+
+use defmt_semihosting as _; // global logger
+
+#[entry]
+fn main() -> ! {
+    defmt::info!("test {=bool}", true);
+
+    defmt::println!("Hello {}{}", "World", '!');
+
+    ExitCode::SUCCESS.exit_process()
+}
+
+defmt::timestamp!("{=u8}", 42);
+
+defmt::core_id!(1);
+
+// like `panic-semihosting` but doesn't print to stdout (that would corrupt the defmt stream)
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    ExitCode::FAILURE.exit_process()
+}
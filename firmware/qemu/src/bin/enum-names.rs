@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+use cortex_m as _;
+use cortex_m_rt::entry;
+use defmt::enum_names;
+use semihosting::process::ExitCode;
+
+use defmt_semihosting as _; // global logger
+
+enum_names! {
+    enum UsbStatus: u8 {
+        Ok = 0,
+        Stall = 1,
+        Nak = 2,
+
+        #[cfg(never)]
+        CfgdOut = 3,
+    }
+}
+
+enum_names! {
+    enum Errno: i32 {
+        EIO = -5,
+        ETIMEDOUT = -110,
+        LARGE = compute_errno(0x7fff_0000),
+    }
+}
+
+const fn compute_errno(x: i32) -> i32 {
+    -x
+}
+
+#[entry]
+fn main() -> ! {
+    defmt::info!("UsbStatus 0: {=u8:enum(UsbStatus)}", 0);
+    defmt::info!("UsbStatus 2: {=u8:enum(UsbStatus)}", 2);
+    defmt::info!("UsbStatus 3: {=u8:enum(UsbStatus)}", 3);
+    defmt::info!("Errno -110: {=i32:enum(Errno)}", -110);
+    defmt::info!(
+        "Errno -0x7fff_0000: {=i32:enum(Errno)}",
+        compute_errno(0x7fff_0000)
+    );
+    defmt::info!("Errno 0: {=i32:enum(Errno)}", 0);
+
+    ExitCode::SUCCESS.exit_process()
+}
+
+// like `panic-semihosting` but doesn't print to stdout (that would corrupt the defmt stream)
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    ExitCode::FAILURE.exit_process()
+}
@@ -0,0 +1,34 @@
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use cortex_m as _;
+use cortex_m_rt::entry;
+use semihosting::process::ExitCode;
+
+use defmt_semihosting as _; // global logger
+
+static TICKS: AtomicU32 = AtomicU32::new(0);
+
+#[entry]
+fn main() -> ! {
+    for i in 0..10u32 {
+        TICKS.store(i * 3, Ordering::Relaxed);
+
+        defmt::info_once!("once {=u32}", i);
+        defmt::warn_every!(4, "every {=u32}", i);
+        defmt::error_throttled!(10, "throttled at tick {=u32}", i * 3);
+    }
+
+    ExitCode::SUCCESS.exit_process()
+}
+
+defmt::throttle_clock!(TICKS.load(Ordering::Relaxed));
+
+// like `panic-semihosting` but doesn't print to stdout (that would corrupt the defmt stream)
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    ExitCode::FAILURE.exit_process()
+}
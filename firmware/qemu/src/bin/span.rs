@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use cortex_m as _;
+use cortex_m_rt::entry;
+use semihosting::process::ExitCode;
+
+use defmt_semihosting as _; // global logger
+
+#[entry]
+fn main() -> ! {
+    {
+        let _boot = defmt::span!("boot");
+        defmt::info!("clocks configured");
+        for sector in 0..2u32 {
+            let _erase = defmt::span!("flash_erase", sector = sector);
+            // erasing a sector takes 100 us
+            COUNT.fetch_add(100, Ordering::Relaxed);
+        }
+    }
+
+    ExitCode::SUCCESS.exit_process()
+}
+
+static COUNT: AtomicU32 = AtomicU32::new(0);
+defmt::timestamp!("{=u32:us}", COUNT.fetch_add(1, Ordering::Relaxed));
+
+// like `panic-semihosting` but doesn't print to stdout (that would corrupt the defmt stream)
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    ExitCode::FAILURE.exit_process()
+}
@@ -21,6 +21,8 @@ struct Symbol<'a> {
     /// * `defmt_println` for logging messages that are always displayed.
     /// * `defmt_trace`, `defmt_debug`, `defmt_info`, `defmt_warn`, `defmt_error` for logging
     ///   messages used at the different log levels.
+    /// * `defmt_span_enter`, `defmt_span_exit` for the frames logged when a `defmt::span!` is
+    ///   entered and exited; they are filtered like `defmt_info` messages.
     /// * `defmt_bitflags` indicates that a format string was generated by a `defmt::bitflags!`
     ///   invocation, and that the decoder should look up possible flags in the binary.
     ///   The data string is of the format `NAME@REPR#NUM`, where `NAME` is the name of the bitflags
@@ -28,6 +30,10 @@ struct Symbol<'a> {
     ///   wire format), and `NUM` is the number of defined bitflag values.
     /// * `defmt_bitflags_value` marks a `static` that holds the value of a bitflags `const`, its
     ///   data field is `STRUCT_NAME::FLAG_NAME`.
+    /// * `defmt_enum_value` marks a `static` that holds a value of a `defmt::enum_names!` table,
+    ///   its data field is `TABLE_NAME::INDEX::VALUE_NAME`.
+    /// * `defmt_core_id` marks the `static` defined by `defmt::core_id!`, its data field is the
+    ///   format of the core ID in each log frame.
     /// * Anything starting with `defmt_` is reserved for use by defmt, other prefixes are free for
     ///   use by third-party apps (but they all should use a prefix!).
     tag: String,
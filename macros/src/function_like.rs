@@ -6,4 +6,5 @@ pub(crate) mod internp;
 pub(crate) mod log;
 pub(crate) mod panic_like;
 pub(crate) mod println;
+pub(crate) mod span;
 pub(crate) mod write;
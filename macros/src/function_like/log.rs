@@ -6,8 +6,12 @@ use syn::{parse_macro_input, parse_quote};
 
 use crate::construct;
 
-use self::env_filter::EnvFilter;
-pub(crate) use self::{args::Args, codegen::Codegen};
+use self::args::RateLimitedArgs;
+pub(crate) use self::{
+    args::{split_fields, Args},
+    codegen::Codegen,
+    env_filter::EnvFilter,
+};
 
 mod args;
 mod codegen;
@@ -19,7 +23,58 @@ pub(crate) fn expand(level: Level, args: TokenStream) -> TokenStream {
         .into()
 }
 
+/// How often the frames of a call site of the rate-limited log macros are logged
+#[derive(Clone, Copy)]
+pub(crate) enum RateLimit {
+    /// `info_once!(...)`: only the first time
+    Once,
+    /// `info_every!(n, ...)`: the first of every `n` times
+    Every,
+    /// `info_throttled!(period, ...)`: at most once per `period` ticks of the throttle clock
+    Throttled,
+}
+
+pub(crate) fn expand_rate_limited(
+    level: Level,
+    rate_limit: RateLimit,
+    args: TokenStream,
+) -> TokenStream {
+    let parser = |input: syn::parse::ParseStream| RateLimitedArgs::parse(input, rate_limit);
+    let RateLimitedArgs { limit, args } = parse_macro_input!(args with parser);
+
+    let guard = match (rate_limit, limit) {
+        (RateLimit::Once, _) => quote!({
+            static ONCE: defmt::export::Once = defmt::export::Once::new();
+            ONCE.should_log()
+        }),
+        (RateLimit::Every, Some(n)) => quote!({
+            static EVERY: defmt::export::Every = defmt::export::Every::new();
+            EVERY.should_log(#n)
+        }),
+        (RateLimit::Throttled, Some(period)) => quote!({
+            static THROTTLED: defmt::export::Throttled = defmt::export::Throttled::new();
+            THROTTLED.should_log(#period)
+        }),
+        (_, None) => unreachable!(),
+    };
+
+    expand_frame(level, level.as_str(), args, Some(guard))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 pub(crate) fn expand_parsed(level: Level, args: Args) -> syn::Result<TokenStream2> {
+    expand_frame(level, level.as_str(), args, None)
+}
+
+/// Expands a log macro whose format string is interned with `tag`, e.g. `info`; if there's a
+/// `guard`, the frame is only encoded when it is `true`.
+pub(crate) fn expand_frame(
+    level: Level,
+    tag: &str,
+    args: Args,
+    guard: Option<TokenStream2>,
+) -> syn::Result<TokenStream2> {
     let format_string = args.format_string.value();
     let (fragments, warnings) =
         match defmt_parser::parse_with_warnings(&format_string, ParserMode::Strict) {
@@ -28,20 +83,35 @@ pub(crate) fn expand_parsed(level: Level, args: Args) -> syn::Result<TokenStream
         };
     let warnings = construct::format_string_warnings(&warnings, args.format_string.span());
 
-    let formatting_exprs = args
-        .formatting_args
-        .map(|punctuated| punctuated.into_iter().collect::<Vec<_>>())
-        .unwrap_or_default();
+    let (mut formatting_exprs, fields) = args::split_fields(args.formatting_args)?;
 
-    let Codegen { patterns, exprs } = Codegen::new(
+    let mut codegen = Codegen::new(
         &fragments,
         formatting_exprs.len(),
         args.format_string.span(),
     )?;
 
+    // the fields become `{@name}` parameters after the message, so that the decoder can tell them
+    // apart while their names are interned along with it
+    let mut format_string = format_string;
+    if !fields.is_empty() {
+        for field in fields {
+            format_string.push_str(&format!("{{@{}}}", field.name));
+            formatting_exprs.push(field.value);
+        }
+        let fragments = defmt_parser::parse(&format_string, ParserMode::Strict)
+            .map_err(|e| syn::Error::new(args.format_string.span(), e.to_string()))?;
+        codegen = Codegen::new(
+            &fragments,
+            formatting_exprs.len(),
+            args.format_string.span(),
+        )?;
+    }
+    let Codegen { patterns, exprs } = codegen;
+
     let header = construct::interned_string(
         &format_string,
-        level.as_str(),
+        tag,
         true,
         Some(level.as_str()),
         &parse_quote!(defmt),
@@ -62,12 +132,26 @@ pub(crate) fn expand_parsed(level: Level, args: Args) -> syn::Result<TokenStream
         )
     };
 
+    let content = match guard {
+        Some(guard) => quote!(if #guard {
+            #content
+        }),
+        None => content,
+    };
+
     let filter_check = env_filter.path_check(level).unwrap_or(quote!(false));
+    // rebuild the crate when a config file changes
+    let config_files = env_filter
+        .config_files()
+        .iter()
+        .map(|path| path.to_string_lossy());
 
     Ok(quote!(
         {
             #warnings
             option_env!("DEFMT_LOG");
+            option_env!("DEFMT_CONFIG");
+            #(const _: &[u8] = include_bytes!(#config_files);)*
             match (#(&(#formatting_exprs)),*) {
                 (#(#patterns),*) => {
                     if #filter_check {
@@ -1,9 +1,12 @@
 use syn::{
+    ext::IdentExt as _,
     parse::{self, Parse, ParseStream},
     punctuated::Punctuated,
-    Expr, LitStr, Token,
+    Expr, ExprAssign, LitStr, Token,
 };
 
+use super::RateLimit;
+
 pub(crate) struct Args {
     pub(crate) format_string: LitStr,
     pub(crate) formatting_args: Option<Punctuated<Expr, Token![,]>>,
@@ -22,3 +25,120 @@ impl Parse for Args {
         })
     }
 }
+
+/// A key-value field, like `rssi = rssi` in `info!("connected", peer = addr, rssi = rssi)`
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) value: Expr,
+}
+
+/// Splits the trailing key-value fields off the formatting arguments.
+pub(crate) fn split_fields(
+    formatting_args: Option<Punctuated<Expr, Token![,]>>,
+) -> syn::Result<(Vec<Expr>, Vec<Field>)> {
+    let mut exprs = vec![];
+    let mut fields: Vec<Field> = vec![];
+
+    for expr in formatting_args.into_iter().flatten() {
+        match as_field(&expr) {
+            Some(field) => {
+                if fields.iter().any(|other| other.name == field.name) {
+                    return Err(syn::Error::new_spanned(
+                        &field.value,
+                        format!("the field `{}` is specified more than once", field.name),
+                    ));
+                }
+                fields.push(field);
+            }
+            None if !fields.is_empty() => {
+                return Err(syn::Error::new_spanned(
+                    expr,
+                    "formatting arguments must come before the key-value fields",
+                ))
+            }
+            None => exprs.push(expr),
+        }
+    }
+
+    Ok((exprs, fields))
+}
+
+/// Returns the field if `expr` is a `name = value` expression.
+fn as_field(expr: &Expr) -> Option<Field> {
+    let Expr::Assign(ExprAssign {
+        attrs, left, right, ..
+    }) = expr
+    else {
+        return None;
+    };
+    let Expr::Path(path) = &**left else {
+        return None;
+    };
+    if !attrs.is_empty() || !path.attrs.is_empty() || path.qself.is_some() {
+        return None;
+    }
+
+    Some(Field {
+        name: path.path.get_ident()?.unraw().to_string(),
+        value: (**right).clone(),
+    })
+}
+
+/// The arguments of a rate-limited log macro: the limit, if any, followed by the usual arguments
+pub(crate) struct RateLimitedArgs {
+    pub(crate) limit: Option<Expr>,
+    pub(crate) args: Args,
+}
+
+impl RateLimitedArgs {
+    pub(crate) fn parse(input: ParseStream, rate_limit: RateLimit) -> parse::Result<Self> {
+        let limit = match rate_limit {
+            RateLimit::Once => None,
+            RateLimit::Every | RateLimit::Throttled => {
+                let limit = input.parse()?;
+                let _comma: Token![,] = input.parse()?;
+                Some(limit)
+            }
+        };
+
+        Ok(Self {
+            limit,
+            args: input.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    #[test]
+    fn splits_trailing_fields() {
+        let (exprs, fields) = split_fields(Some(parse_quote!(a, b + 1, peer = addr, r#type = 2)))
+            .map_err(|e| e.to_string())
+            .unwrap();
+
+        assert_eq!(exprs, [parse_quote!(a), parse_quote!(b + 1)] as [Expr; 2]);
+        let names = fields.iter().map(|field| field.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["peer", "type"]);
+        assert_eq!(fields[0].value, parse_quote!(addr));
+    }
+
+    #[test]
+    fn rejects_formatting_argument_after_field() {
+        let error = split_fields(Some(parse_quote!(peer = addr, a)))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("must come before"));
+    }
+
+    #[test]
+    fn rejects_duplicate_field() {
+        let error = split_fields(Some(parse_quote!(a = 1, a = 2)))
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("more than once"));
+    }
+}
@@ -15,10 +15,7 @@ impl Codegen {
     ) -> syn::Result<Self> {
         let params = fragments
             .iter()
-            .filter_map(|frag| match frag {
-                Fragment::Parameter(param) => Some(param.clone()),
-                Fragment::Literal(_) => None,
-            })
+            .filter_map(|frag| frag.parameter().cloned())
             .collect::<Vec<_>>();
 
         let expected_arg_count = params
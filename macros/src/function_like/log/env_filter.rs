@@ -1,20 +1,27 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     env,
+    path::{Path, PathBuf},
 };
 
 use defmt_parser::Level;
-use proc_macro2::{Span, TokenStream as TokenStream2};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use quote::quote;
 
-use self::parse::{Entry, LogLevelOrOff, ModulePath};
+use self::{
+    config::Config,
+    parse::{Entry, LogLevelOrOff, ModulePath},
+};
 
+mod config;
 mod parse;
 
 #[derive(Debug)]
 pub(crate) struct EnvFilter {
     // to keep the module paths sorted by length we use a btreemap
     entries: BTreeMap<ModulePath, LogLevelOrOff>,
+    /// Config files the entries were read from
+    config_files: Vec<PathBuf>,
 }
 
 impl EnvFilter {
@@ -26,10 +33,18 @@ impl EnvFilter {
                 "`CARGO_CRATE_NAME` env var is not set",
             ));
         };
-        Self::new(defmt_log.as_deref(), &cargo_crate_name)
+        let config = match env::var_os("CARGO_MANIFEST_DIR") {
+            Some(manifest_dir) => Config::load(
+                Path::new(&manifest_dir),
+                env::var_os("DEFMT_CONFIG").as_deref().map(Path::new),
+            )?,
+            None => Config::default(),
+        };
+        Self::new(defmt_log.as_deref(), config, &cargo_crate_name)
     }
 
-    fn new(defmt_log: Option<&str>, cargo_crate_name: &str) -> syn::Result<Self> {
+    /// `DEFMT_LOG` takes precedence over the `config`.
+    fn new(defmt_log: Option<&str>, config: Config, cargo_crate_name: &str) -> syn::Result<Self> {
         // match `env_logger` behavior
         const LEVEL_WHEN_LEVEL_IS_NOT_SPECIFIED: LogLevelOrOff = Some(Level::Trace);
         const LEVEL_WHEN_NOTHING_IS_SPECIFIED: LogLevelOrOff = Some(Level::Error);
@@ -38,34 +53,45 @@ impl EnvFilter {
 
         let mut entries = BTreeMap::new();
         let mut fallback_log_level = None;
-        if let Some(input) = defmt_log {
-            for entry in parse::defmt_log(input) {
-                let (modpath, level) = match entry? {
-                    Entry::LogLevel(log_level) => {
-                        if fallback_log_level.is_none() {
-                            fallback_log_level = Some(log_level);
-                        }
-                        continue;
+        let env_entries = defmt_log.into_iter().flat_map(parse::defmt_log);
+        let config_entries = config.entries.into_iter().map(Ok);
+        for entry in env_entries.chain(config_entries) {
+            let (modpath, level) = match entry? {
+                Entry::LogLevel(log_level) => {
+                    if fallback_log_level.is_none() {
+                        fallback_log_level = Some(log_level);
                     }
-                    Entry::ModulePath(module) => (module, LEVEL_WHEN_LEVEL_IS_NOT_SPECIFIED),
-                    Entry::ModulePathLogLevel {
-                        module_path,
-                        log_level,
-                    } => (module_path, log_level),
-                };
-
-                if modpath.crate_name() == caller_crate && !entries.contains_key(&modpath) {
-                    entries.insert(modpath, level);
+                    continue;
                 }
+                Entry::ModulePath(module) => (module, LEVEL_WHEN_LEVEL_IS_NOT_SPECIFIED),
+                Entry::ModulePathLogLevel {
+                    module_path,
+                    log_level,
+                } => (module_path, log_level),
+            };
+
+            if modpath.matches_crate(caller_crate) && !entries.contains_key(&modpath) {
+                entries.insert(modpath, level);
             }
         }
 
-        let modpath = ModulePath::from_crate_name(caller_crate);
-        entries
-            .entry(modpath)
-            .or_insert_with(|| fallback_log_level.unwrap_or(LEVEL_WHEN_NOTHING_IS_SPECIFIED));
+        // a crate pattern like `embassy_*` is more specific than the fallback log level
+        if !entries.keys().any(ModulePath::is_crate) {
+            let modpath = ModulePath::from_crate_name(caller_crate);
+            let level = fallback_log_level.unwrap_or(LEVEL_WHEN_NOTHING_IS_SPECIFIED);
+            entries.insert(modpath, level);
+        }
+
+        Ok(EnvFilter {
+            entries,
+            config_files: config.files,
+        })
+    }
 
-        Ok(EnvFilter { entries })
+    /// Config files that the filter was read from, and that the crate has to be rebuilt for when
+    /// they change.
+    pub(crate) fn config_files(&self) -> &[PathBuf] {
+        &self.config_files
     }
 
     /// Builds a compile-time check that returns `true` when `module_path!` can emit logs at the
@@ -102,7 +128,11 @@ impl EnvFilter {
             .iter()
             .rev()
             .map(|(&module_path, criteria)| {
-                let check = codegen_is_inside_of_check(&module_path.to_string());
+                let check = if module_path.is_pattern() {
+                    codegen_matches_pattern_check(&module_path.to_string())
+                } else {
+                    codegen_is_inside_of_check(&module_path.to_string())
+                };
                 let retval = match criteria {
                     Criteria::Accept => quote!(true),
                     Criteria::Reject => quote!(false),
@@ -194,6 +224,13 @@ fn codegen_is_inside_of_check(parent_module_path: &str) -> TokenStream2 {
     })
 }
 
+// like `codegen_is_inside_of_check`, but `pattern` contains `*` wildcards, which are matched by
+// a `const fn` instead of unrolled byte comparisons
+fn codegen_matches_pattern_check(pattern: &str) -> TokenStream2 {
+    let pattern = Literal::byte_string(pattern.as_bytes());
+    quote!(defmt::export::module_path_matches(module_path, #pattern))
+}
+
 #[cfg(test)]
 mod tests {
     use maplit::btreeset;
@@ -203,7 +240,8 @@ mod tests {
 
     #[test]
     fn when_duplicates_entries_in_defmt_log_use_last_entry() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate=info,krate=debug"), "krate")?;
+        let env_filter =
+            EnvFilter::new(Some("krate=info,krate=debug"), Config::default(), "krate")?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -215,7 +253,7 @@ mod tests {
 
     #[test]
     fn when_empty_defmt_log_use_error() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(None, "krate")?;
+        let env_filter = EnvFilter::new(None, Config::default(), "krate")?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -227,7 +265,7 @@ mod tests {
 
     #[test]
     fn when_no_level_in_defmt_log_use_trace() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate"), "krate")?;
+        let env_filter = EnvFilter::new(Some("krate"), Config::default(), "krate")?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -238,7 +276,7 @@ mod tests {
 
     #[test]
     fn when_level_in_defmt_log_use_it() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate=info"), "krate")?;
+        let env_filter = EnvFilter::new(Some("krate=info"), Config::default(), "krate")?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -250,7 +288,7 @@ mod tests {
 
     #[test]
     fn when_only_level_is_specified_in_defmt_log_it_applies_to_all_crates() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("info"), "krate")?;
+        let env_filter = EnvFilter::new(Some("info"), Config::default(), "krate")?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -262,7 +300,7 @@ mod tests {
 
     #[test]
     fn moduleless_level_has_lower_precedence() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate=info,warn"), "krate")?;
+        let env_filter = EnvFilter::new(Some("krate=info,warn"), Config::default(), "krate")?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -274,7 +312,8 @@ mod tests {
 
     #[test]
     fn moduleless_level_behaves_like_a_krate_level_pair() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate::module=info,warn"), "krate")?;
+        let env_filter =
+            EnvFilter::new(Some("krate::module=info,warn"), Config::default(), "krate")?;
         let expected = [
             ModulePath::parse("krate"),
             ModulePath::parse("krate::module"),
@@ -296,7 +335,11 @@ mod tests {
 
     #[test]
     fn module_paths_different_levels() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate=info,krate::module=debug"), "krate")?;
+        let env_filter = EnvFilter::new(
+            Some("krate=info,krate::module=debug"),
+            Config::default(),
+            "krate",
+        )?;
 
         let expected = [
             ModulePath::parse("krate"),
@@ -319,7 +362,7 @@ mod tests {
 
     #[test]
     fn blanket_off() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("off"), "krate")?;
+        let env_filter = EnvFilter::new(Some("off"), Config::default(), "krate")?;
 
         assert_eq!(btreeset![], env_filter.modules_on_for(Level::Error));
 
@@ -333,7 +376,8 @@ mod tests {
 
     #[test]
     fn blanket_off_plus_override() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate::module=error,off"), "krate")?;
+        let env_filter =
+            EnvFilter::new(Some("krate::module=error,off"), Config::default(), "krate")?;
 
         let expected = [ModulePath::parse("krate::module")];
         assert_eq!(
@@ -355,6 +399,7 @@ mod tests {
     fn child_can_be_more_restrictive_than_parent() -> syn::Result<()> {
         let env_filter = EnvFilter::new(
             Some("debug,krate::parent=trace,krate::parent::child=debug"),
+            Config::default(),
             "krate",
         )?;
 
@@ -377,7 +422,7 @@ mod tests {
 
     #[test]
     fn does_not_match_partial_crate_name() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("fooo=warn"), "foo")?;
+        let env_filter = EnvFilter::new(Some("fooo=warn"), Config::default(), "foo")?;
         let expected = [ModulePath::parse("foo")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -387,11 +432,75 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn crate_pattern_applies_to_matching_crates() -> syn::Result<()> {
+        let env_filter = EnvFilter::new(
+            Some("info,embassy_*=warn"),
+            Config::default(),
+            "embassy_net",
+        )?;
+        let expected = [ModulePath::parse("embassy_*")];
+        assert_eq!(
+            expected.iter().collect::<BTreeSet<_>>(),
+            env_filter.modules_on_for(Level::Warn)
+        );
+        assert_eq!(btreeset![], env_filter.modules_on_for(Level::Info));
+
+        let env_filter = EnvFilter::new(Some("info,embassy_*=warn"), Config::default(), "app")?;
+        let expected = [ModulePath::parse("app")];
+        assert_eq!(
+            expected.iter().collect::<BTreeSet<_>>(),
+            env_filter.modules_on_for(Level::Info)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn module_pattern_applies_to_every_crate() -> syn::Result<()> {
+        let env_filter =
+            EnvFilter::new(Some("warn,*::drivers::*=debug"), Config::default(), "app")?;
+        let expected = [ModulePath::parse("*::drivers::*")];
+        assert_eq!(
+            expected.iter().collect::<BTreeSet<_>>(),
+            env_filter.modules_on_for(Level::Debug)
+        );
+        let expected = [ModulePath::parse("app")];
+        assert_eq!(
+            expected.iter().collect::<BTreeSet<_>>(),
+            env_filter.modules_off_for(Level::Debug)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn plain_path_overrides_pattern() -> syn::Result<()> {
+        let env_filter = EnvFilter::new(
+            Some("krate::drivers::uart=error,krate::drivers::*=trace"),
+            Config::default(),
+            "krate",
+        )?;
+        let paths = env_filter.entries.keys().collect::<Vec<_>>();
+        // `path_check` checks the last (most specific) path first
+        assert_eq!(
+            [
+                &ModulePath::parse("krate"),
+                &ModulePath::parse("krate::drivers::*"),
+                &ModulePath::parse("krate::drivers::uart"),
+            ],
+            paths.as_slice()
+        );
+        Ok(())
+    }
+
     // doesn't affect runtime performance but it makes the expanded code smaller
     #[ignore = "TODO(P-low/optimization): impl & more test cases"]
     #[test]
     fn when_module_paths_with_same_level_remove_inner_ones() -> syn::Result<()> {
-        let env_filter = EnvFilter::new(Some("krate=info,krate::module=info"), "krate")?;
+        let env_filter = EnvFilter::new(
+            Some("krate=info,krate::module=info"),
+            Config::default(),
+            "krate",
+        )?;
         let expected = [ModulePath::parse("krate")];
         assert_eq!(
             expected.iter().collect::<BTreeSet<_>>(),
@@ -0,0 +1,276 @@
+//! Log filters from `defmt.toml` and `[package.metadata.defmt]`, which complement `DEFMT_LOG`.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use proc_macro2::Span;
+
+use super::parse::{self, Entry, ModulePath};
+
+/// The config file that is looked up in the crate's directory and its ancestors.
+const CONFIG_FILE_NAME: &str = "defmt.toml";
+
+/// The configs loaded so far, by manifest dir and `DEFMT_CONFIG`; errors are kept as messages.
+///
+/// The proc macro library is loaded for each crate that is compiled, so all log macro call sites
+/// of a crate share one entry.
+static LOADED: Mutex<BTreeMap<LoadArgs, Result<Config, String>>> = Mutex::new(BTreeMap::new());
+
+/// The manifest dir and the config file set by `DEFMT_CONFIG`
+type LoadArgs = (PathBuf, Option<PathBuf>);
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Config {
+    /// The filter entries, highest precedence first (like `parse::defmt_log`)
+    pub(super) entries: Vec<Entry>,
+    /// The files the entries were read from; the crate has to be rebuilt when they change
+    pub(super) files: Vec<PathBuf>,
+}
+
+impl Config {
+    /// Loads the config like [`Config::read`], or returns the one loaded before for the same
+    /// arguments.
+    ///
+    /// Only the first load returns the `files`, as the crate only needs to track them once.
+    pub(crate) fn load(manifest_dir: &Path, config_file: Option<&Path>) -> syn::Result<Self> {
+        let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+        let key = (manifest_dir.to_owned(), config_file.map(Path::to_owned));
+        let config = match loaded.get(&key) {
+            Some(Ok(config)) => Ok(Config {
+                entries: config.entries.clone(),
+                files: vec![],
+            }),
+            Some(Err(message)) => Err(message.clone()),
+            None => {
+                let config = Self::read(manifest_dir, config_file);
+                loaded.insert(key, config.clone());
+                config
+            }
+        };
+        config.map_err(|message| syn::Error::new(Span::call_site(), message))
+    }
+
+    /// Reads the `log` table of `[package.metadata.defmt]` in the crate's manifest and of the
+    /// config file, in that order of precedence.
+    ///
+    /// The config file is `config_file` (the `DEFMT_CONFIG` env var) if set, otherwise the first
+    /// `defmt.toml` found in `manifest_dir` or its ancestors up to the workspace root.
+    fn read(manifest_dir: &Path, config_file: Option<&Path>) -> Result<Self, String> {
+        let mut config = Config::default();
+
+        let manifest_path = manifest_dir.join("Cargo.toml");
+        if let Some(manifest) = read(&manifest_path)? {
+            let log = manifest
+                .get("package")
+                .and_then(|package| package.get("metadata"))
+                .and_then(|metadata| metadata.get("defmt"))
+                .and_then(|defmt| defmt.get("log"));
+            if let Some(log) = log {
+                config.add_log_table(log, &manifest_path)?;
+                config.files.push(manifest_path);
+            }
+        }
+
+        let config_path = match config_file {
+            Some(path) => Some(path.to_owned()),
+            None => find_config_file(manifest_dir),
+        };
+        if let Some(config_path) = config_path {
+            let Some(table) = read(&config_path)? else {
+                return Err(error(
+                    &config_path,
+                    "file not found (set by `DEFMT_CONFIG`)",
+                ));
+            };
+            if let Some(log) = table.get("log") {
+                config.add_log_table(log, &config_path)?;
+            }
+            config.files.push(config_path);
+        }
+
+        Ok(config)
+    }
+
+    /// Adds the entries of a `log` table like this one:
+    ///
+    /// ```toml
+    /// [log]
+    /// level = "info"
+    ///
+    /// [log.modules]
+    /// krate = "debug"
+    /// "krate::noisy" = "off"
+    /// ```
+    fn add_log_table(&mut self, log: &toml::Value, path: &Path) -> Result<(), String> {
+        let Some(log) = log.as_table() else {
+            return Err(error(path, "`log` must be a table"));
+        };
+
+        for (key, value) in log {
+            match key.as_str() {
+                "level" => {
+                    let log_level = parse_log_level(value, path)?;
+                    self.entries.push(Entry::LogLevel(log_level));
+                }
+                "modules" => {
+                    let Some(modules) = value.as_table() else {
+                        return Err(error(path, "`log.modules` must be a table"));
+                    };
+                    for (module_path, log_level) in modules {
+                        if module_path.is_empty() {
+                            return Err(error(path, "module path cannot be an empty string"));
+                        }
+                        self.entries.push(Entry::ModulePathLogLevel {
+                            module_path: ModulePath::parse(module_path),
+                            log_level: parse_log_level(log_level, path)?,
+                        });
+                    }
+                }
+                _ => {
+                    return Err(error(
+                        path,
+                        &format!("unknown key `log.{key}`. expected one of: level, modules"),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn find_config_file(manifest_dir: &Path) -> Option<PathBuf> {
+    workspace_dirs(manifest_dir)
+        .into_iter()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+/// Returns `manifest_dir` and its ancestors up to the workspace root, i.e. the closest one whose
+/// `Cargo.toml` has a `[workspace]` table; just `manifest_dir` if there is no such ancestor.
+fn workspace_dirs(manifest_dir: &Path) -> Vec<&Path> {
+    let mut dirs = vec![];
+    for dir in manifest_dir.ancestors() {
+        dirs.push(dir);
+        let manifest = read(&dir.join("Cargo.toml")).ok().flatten();
+        if manifest.is_some_and(|manifest| manifest.contains_key("workspace")) {
+            return dirs;
+        }
+    }
+    vec![manifest_dir]
+}
+
+/// Reads and parses a TOML file; returns `None` if it doesn't exist.
+fn read(path: &Path) -> Result<Option<toml::Table>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(error(path, &e.to_string())),
+    };
+
+    contents
+        .parse()
+        .map(Some)
+        .map_err(|e: toml::de::Error| error(path, e.message()))
+}
+
+fn parse_log_level(value: &toml::Value, path: &Path) -> Result<parse::LogLevelOrOff, String> {
+    let Some(log_level) = value.as_str() else {
+        return Err(error(path, "log levels must be strings"));
+    };
+
+    parse::parse_log_level(log_level).map_err(|()| {
+        error(
+            path,
+            &format!(
+                "unknown log level `{log_level}`. \
+                expected one of: off, error, info, warn, debug, trace"
+            ),
+        )
+    })
+}
+
+fn error(path: &Path, message: &str) -> String {
+    format!("defmt log filter in `{}`: {message}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use defmt_parser::Level;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn log_table(input: &str) -> Result<Vec<Entry>, String> {
+        let table = input.parse::<toml::Table>().unwrap();
+        let mut config = Config::default();
+        config.add_log_table(&table["log"], Path::new("defmt.toml"))?;
+        Ok(config.entries)
+    }
+
+    #[test]
+    fn parses_log_table() -> Result<(), String> {
+        let entries = log_table(
+            r#"
+            [log]
+            level = "warn"
+
+            [log.modules]
+            krate = "debug"
+            "krate::module" = "off"
+            "#,
+        )?;
+
+        assert_eq!(
+            [
+                Entry::LogLevel(Some(Level::Warn)),
+                Entry::ModulePathLogLevel {
+                    module_path: ModulePath::parse("krate"),
+                    log_level: Some(Level::Debug),
+                },
+                Entry::ModulePathLogLevel {
+                    module_path: ModulePath::parse("krate::module"),
+                    log_level: None,
+                },
+            ],
+            entries.as_slice()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unknown_log_level() {
+        let error = log_table("log.level = \"loud\"").unwrap_err();
+        assert!(error.contains("unknown log level"));
+    }
+
+    #[test]
+    fn rejects_unknown_key() {
+        let error = log_table("log.lvl = \"info\"").unwrap_err();
+        assert!(error.contains("unknown key `log.lvl`"));
+    }
+
+    #[test]
+    fn finds_config_file_up_to_workspace_root() {
+        let root = std::env::temp_dir().join(format!("defmt-config-{}", std::process::id()));
+        let manifest_dir = root.join("workspace/crates/krate");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        fs::write(root.join("workspace/Cargo.toml"), "[workspace]").unwrap();
+        fs::write(manifest_dir.join("Cargo.toml"), "[package]").unwrap();
+
+        // `crates` has no manifest, but belongs to the workspace
+        fs::write(root.join("workspace/defmt.toml"), "").unwrap();
+        let found = find_config_file(&manifest_dir);
+        // outside of the workspace
+        fs::rename(root.join("workspace/defmt.toml"), root.join("defmt.toml")).unwrap();
+        let outside = find_config_file(&manifest_dir);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(Some(root.join("workspace/defmt.toml")), found);
+        assert_eq!(None, outside);
+    }
+}
@@ -1,13 +1,14 @@
 use defmt_parser::Level;
 use proc_macro2::Span;
-use std::fmt;
+use std::{cmp::Ordering, fmt};
 use syn::Ident;
 
 // None = "off" pseudo-level
 pub(crate) type LogLevelOrOff = Option<Level>;
 
 // NOTE this is simpler than `syn::Path`; we do not want to accept e.g. `Vec::<Ty>::new`
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+/// A module path, whose segments may contain `*` wildcards, e.g. `embassy_*` or `*::drivers::*`
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct ModulePath {
     segments: Vec<String>,
 }
@@ -43,7 +44,7 @@ pub(crate) fn defmt_log(input: &str) -> impl Iterator<Item = syn::Result<Entry>>
         })
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) enum Entry {
     LogLevel(LogLevelOrOff),
     ModulePath(ModulePath),
@@ -81,6 +82,43 @@ impl ModulePath {
     pub(super) fn crate_name(&self) -> &str {
         &self.segments[0]
     }
+
+    /// Whether this path applies to the crate `crate_name`
+    pub(super) fn matches_crate(&self, crate_name: &str) -> bool {
+        glob_matches(self.crate_name(), crate_name)
+    }
+
+    /// Whether the path is a crate, i.e. has no modules
+    pub(super) fn is_crate(&self) -> bool {
+        self.segments.len() == 1
+    }
+
+    /// Whether any of the segments contains a `*` wildcard
+    pub(super) fn is_pattern(&self) -> bool {
+        self.segments.iter().any(|segment| segment.contains('*'))
+    }
+}
+
+/// Sorts the more specific paths last: inner modules after outer ones, and, at the same depth,
+/// plain segments after patterns
+impl Ord for ModulePath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |path: &Self| {
+            let segments = path
+                .segments
+                .iter()
+                .map(|segment| (!segment.contains('*'), segment.clone()))
+                .collect::<Vec<_>>();
+            (path.segments.len(), segments)
+        };
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for ModulePath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 impl fmt::Display for ModulePath {
@@ -89,7 +127,7 @@ impl fmt::Display for ModulePath {
     }
 }
 
-fn parse_log_level(input: &str) -> Result<LogLevelOrOff, ()> {
+pub(super) fn parse_log_level(input: &str) -> Result<LogLevelOrOff, ()> {
     Ok(Some(match input {
         "debug" => Level::Debug,
         "error" => Level::Error,
@@ -102,10 +140,26 @@ fn parse_log_level(input: &str) -> Result<LogLevelOrOff, ()> {
 }
 
 fn validate_identifier(input: &str) {
-    syn::parse_str::<Ident>(input)
+    // a `*` can stand for any part of an identifier, including the first character
+    syn::parse_str::<Ident>(&input.replace('*', "x"))
         .unwrap_or_else(|_| panic!("`{input}` is not a valid identifier"));
 }
 
+/// Matches `input` against `pattern`, in which `*` matches any number of characters
+fn glob_matches(pattern: &str, input: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == input,
+        Some((prefix, rest)) => {
+            let Some(input) = input.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=input.len())
+                .filter(|&i| input.is_char_boundary(i))
+                .any(|i| glob_matches(rest, &input[i..]))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -151,6 +205,52 @@ mod tests {
         assert_eq!(expected, paths);
     }
 
+    #[test]
+    fn after_sorting_patterns_appear_before_plain_paths() {
+        let mut paths = [
+            ModulePath::parse("krate::module"),
+            ModulePath::parse("krate::*::inner"),
+            ModulePath::parse("krate::*"),
+            ModulePath::parse("*"),
+            ModulePath::parse("krate"),
+        ];
+        paths.sort();
+
+        let expected = [
+            ModulePath::parse("*"),
+            ModulePath::parse("krate"),
+            ModulePath::parse("krate::*"),
+            ModulePath::parse("krate::module"),
+            ModulePath::parse("krate::*::inner"),
+        ];
+        assert_eq!(expected, paths);
+    }
+
+    #[rstest]
+    #[case::wildcard("*", "embassy_net", true)]
+    #[case::prefix("embassy_*", "embassy_net", true)]
+    #[case::suffix("*_hal", "nrf52840_hal", true)]
+    #[case::infix("nrf*_hal", "nrf52840_hal", true)]
+    #[case::empty_match("embassy*", "embassy", true)]
+    #[case::no_match("embassy_*", "embassy", false)]
+    #[case::plain("krate", "krate", true)]
+    #[case::partial_plain("krate", "krate2", false)]
+    fn modpath_matches_crate(#[case] pattern: &str, #[case] krate: &str, #[case] expected: bool) {
+        assert_eq!(expected, ModulePath::parse(pattern).matches_crate(krate));
+    }
+
+    #[test]
+    fn accepts_patterns() {
+        ModulePath::parse("*::drivers::*");
+        ModulePath::parse("embassy_*::r#mod");
+    }
+
+    #[test]
+    #[should_panic = "not a valid identifier"]
+    fn rejects_invalid_pattern() {
+        ModulePath::parse("krate::*-driver");
+    }
+
     #[test]
     fn accepts_raw_identifier() {
         ModulePath::parse("krate::r#mod");
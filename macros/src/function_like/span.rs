@@ -0,0 +1,60 @@
+use defmt_parser::{Fragment, Level, ParserMode};
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote};
+
+use crate::{
+    construct,
+    function_like::log::{self, Args, EnvFilter},
+};
+
+/// Spans are filtered like log statements at this level
+const LEVEL: Level = Level::Info;
+
+pub(crate) fn expand(args: TokenStream) -> TokenStream {
+    expand_parsed(parse_macro_input!(args as Args))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_parsed(args: Args) -> syn::Result<TokenStream2> {
+    let name = args.format_string.value();
+    let fragments = defmt_parser::parse(&name, ParserMode::Strict)
+        .map_err(|e| syn::Error::new(args.format_string.span(), e.to_string()))?;
+    // the decoder pairs the enter and exit frames by name
+    if fragments
+        .iter()
+        .any(|fragment| !matches!(fragment, Fragment::Literal(_)))
+    {
+        return Err(syn::Error::new(
+            args.format_string.span(),
+            "span names can't contain formatting parameters; use key-value fields instead",
+        ));
+    }
+    let (formatting_exprs, _) = log::split_fields(args.formatting_args.clone())?;
+    if let Some(expr) = formatting_exprs.first() {
+        return Err(syn::Error::new_spanned(
+            expr,
+            "spans only take key-value fields, like `sector = n`",
+        ));
+    }
+
+    // the enter frame carries the fields, the exit frame only the name
+    let enter = log::expand_frame(LEVEL, "span_enter", args, None)?;
+    let exit = construct::interned_string(
+        &name,
+        "span_exit",
+        true,
+        Some(LEVEL.as_str()),
+        &parse_quote!(defmt),
+    );
+    let filter_check = EnvFilter::from_env_var()?
+        .path_check(LEVEL)
+        .unwrap_or(quote!(false));
+
+    Ok(quote!({
+        #enter
+        defmt::Span::new(if #filter_check { Some(#exit) } else { None })
+    }))
+}
@@ -1,4 +1,7 @@
 //! Procedural macros that expand to items
 
 pub(crate) mod bitflags;
+pub(crate) mod core_id;
+pub(crate) mod enum_names;
+pub(crate) mod throttle_clock;
 pub(crate) mod timestamp;
@@ -0,0 +1,36 @@
+use proc_macro::TokenStream;
+use quote::format_ident;
+use quote::quote;
+use syn::{parse_macro_input, Expr};
+
+use crate::construct;
+
+pub(crate) fn expand(args: TokenStream) -> TokenStream {
+    let expr = parse_macro_input!(args as Expr);
+
+    let var_name = format_ident!("S");
+    let var_item = construct::static_variable(&var_name, "{=u8}", "core_id", None);
+
+    quote!(
+        const _: () = {
+            #[export_name = "_defmt_core_id"]
+            #[inline(never)]
+            fn defmt_core_id(_fmt: defmt::Formatter<'_>) {
+                let core_id: u8 = #expr;
+                defmt::export::u8(&core_id);
+            }
+
+            #var_item;
+
+            // Unique symbol name to prevent multiple `core_id!` invocations in the crate graph.
+            // Retaining this symbol also retains `#var_name` through the reference below. The
+            // linker script's `EXTERN` directive provides the same guarantee when it is used.
+            #[used]
+            #[no_mangle]
+            #[cfg_attr(target_os = "macos", link_section = ".defmt,end.core_id")]
+            #[cfg_attr(not(target_os = "macos"), link_section = ".defmt.end.core_id")]
+            static __DEFMT_MARKER_CORE_ID_WAS_DEFINED: &u8 = &#var_name;
+        };
+    )
+    .into()
+}
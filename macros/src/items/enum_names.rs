@@ -0,0 +1,63 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse_macro_input;
+
+use crate::construct;
+
+use self::input::Input;
+
+mod input;
+
+pub(crate) fn expand(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as Input);
+
+    let ty = input.ty();
+    let value_statics = codegen_value_statics(&input);
+    quote!(
+        const _: () = {
+            fn assert<T: defmt::export::Int>() {}
+            assert::<#ty>;
+
+            #(#value_statics)*
+        };
+    )
+    .into()
+}
+
+fn codegen_value_statics(input: &Input) -> Vec<TokenStream2> {
+    input
+        .values()
+        .enumerate()
+        .map(|(i, value)| {
+            let cfg_attrs = value.cfg_attrs();
+            let var_name = value.ident();
+            let expr = value.expr();
+            let repr_ty = input.ty();
+
+            let sym_name = construct::mangled_symbol_name(
+                "enum_value",
+                &format!("{}::{i}::{}", input.ident(), value.ident()),
+            );
+
+            quote! {
+                #(#cfg_attrs)*
+                // These values are decoder metadata and have no runtime reference that would
+                // otherwise keep them when no defmt linker script is used.
+                // MSVC represents `#[used]` as `/INCLUDE:<symbol>`, but `link.exe` fails to
+                // resolve defmt's JSON symbol names when they are forced this way.
+                #[cfg_attr(not(target_env = "msvc"), used)]
+                #[cfg_attr(target_os = "macos", link_section = ".defmt,end")]
+                #[cfg_attr(not(target_os = "macos"), link_section = ".defmt.end")]
+                #[export_name = #sym_name]
+                #[allow(non_upper_case_globals)]
+                static #var_name: u128 = {
+                    // Coerce the value to the table's type first, so that e.g. `1 << 31` isn't
+                    // evaluated as an (overflowing) `i32`. Signed values are sign-extended.
+                    let coerced_value: #repr_ty = #expr;
+                    coerced_value as u128
+                };
+            }
+        })
+        .collect::<Vec<_>>()
+}
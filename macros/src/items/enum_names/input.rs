@@ -0,0 +1,80 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token, Attribute, Expr, Ident, Token, Type,
+};
+
+#[allow(dead_code)]
+pub(super) struct Input {
+    enum_token: Token![enum],
+    ident: Ident,
+    colon_token: Token![:],
+    ty: Type,
+    brace_token: token::Brace,
+    values: Punctuated<Value, Token![,]>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let values;
+        Ok(Self {
+            enum_token: input.parse()?,
+            ident: input.parse()?,
+            colon_token: input.parse()?,
+            ty: input.parse()?,
+            brace_token: syn::braced!(values in input),
+            values: Punctuated::parse_terminated(&values)?,
+        })
+    }
+}
+
+impl Input {
+    pub(super) fn values(&self) -> impl Iterator<Item = &Value> {
+        self.values.iter()
+    }
+
+    pub(super) fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    pub(super) fn ty(&self) -> &Type {
+        &self.ty
+    }
+}
+
+#[allow(dead_code)]
+pub(super) struct Value {
+    cfg_attrs: Vec<Attribute>,
+    ident: Ident,
+    eq_token: Token![=],
+    expr: Expr,
+}
+
+impl Parse for Value {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = Attribute::parse_outer(input)?;
+        Ok(Self {
+            cfg_attrs: attrs
+                .into_iter()
+                .filter(|attr| attr.path().is_ident("cfg"))
+                .collect(),
+            ident: input.parse()?,
+            eq_token: input.parse()?,
+            expr: input.parse()?,
+        })
+    }
+}
+
+impl Value {
+    pub(super) fn cfg_attrs(&self) -> &[Attribute] {
+        &self.cfg_attrs
+    }
+
+    pub(super) fn ident(&self) -> &Ident {
+        &self.ident
+    }
+
+    pub(super) fn expr(&self) -> &Expr {
+        &self.expr
+    }
+}
@@ -0,0 +1,20 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Expr};
+
+pub(crate) fn expand(args: TokenStream) -> TokenStream {
+    let expr = parse_macro_input!(args as Expr);
+
+    quote!(
+        const _: () = {
+            // the `*_throttled!` macros fail to link if no crate defines the clock, and linking
+            // fails with a duplicate symbol if more than one crate does
+            #[export_name = "_defmt_throttle_clock"]
+            #[inline(never)]
+            fn defmt_throttle_clock() -> u32 {
+                #expr
+            }
+        };
+    )
+    .into()
+}
@@ -7,6 +7,8 @@ use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
+use crate::function_like::log::RateLimit;
+
 mod attributes;
 mod cargo;
 mod construct;
@@ -131,6 +133,91 @@ pub fn warn(args: TokenStream) -> TokenStream {
 pub fn error(args: TokenStream) -> TokenStream {
     function_like::log::expand(Level::Error, args)
 }
+/* ### `_once` variants */
+
+#[proc_macro]
+pub fn trace_once(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Trace, RateLimit::Once, args)
+}
+
+#[proc_macro]
+pub fn debug_once(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Debug, RateLimit::Once, args)
+}
+
+#[proc_macro]
+pub fn info_once(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Info, RateLimit::Once, args)
+}
+
+#[proc_macro]
+pub fn warn_once(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Warn, RateLimit::Once, args)
+}
+
+#[proc_macro]
+pub fn error_once(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Error, RateLimit::Once, args)
+}
+
+/* ### `_every` variants */
+
+#[proc_macro]
+pub fn trace_every(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Trace, RateLimit::Every, args)
+}
+
+#[proc_macro]
+pub fn debug_every(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Debug, RateLimit::Every, args)
+}
+
+#[proc_macro]
+pub fn info_every(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Info, RateLimit::Every, args)
+}
+
+#[proc_macro]
+pub fn warn_every(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Warn, RateLimit::Every, args)
+}
+
+#[proc_macro]
+pub fn error_every(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Error, RateLimit::Every, args)
+}
+
+/* ### `_throttled` variants */
+
+#[proc_macro]
+pub fn trace_throttled(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Trace, RateLimit::Throttled, args)
+}
+
+#[proc_macro]
+pub fn debug_throttled(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Debug, RateLimit::Throttled, args)
+}
+
+#[proc_macro]
+pub fn info_throttled(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Info, RateLimit::Throttled, args)
+}
+
+#[proc_macro]
+pub fn warn_throttled(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Warn, RateLimit::Throttled, args)
+}
+
+#[proc_macro]
+pub fn error_throttled(args: TokenStream) -> TokenStream {
+    function_like::log::expand_rate_limited(Level::Error, RateLimit::Throttled, args)
+}
+
+#[proc_macro]
+pub fn span(args: TokenStream) -> TokenStream {
+    function_like::span::expand(args)
+}
 /* ## end of logging macros */
 
 #[proc_macro]
@@ -177,7 +264,22 @@ pub fn bitflags(ts: TokenStream) -> TokenStream {
     items::bitflags::expand(ts)
 }
 
+#[proc_macro]
+pub fn core_id(args: TokenStream) -> TokenStream {
+    items::core_id::expand(args)
+}
+
+#[proc_macro]
+pub fn enum_names(ts: TokenStream) -> TokenStream {
+    items::enum_names::expand(ts)
+}
+
 #[proc_macro]
 pub fn timestamp(args: TokenStream) -> TokenStream {
     items::timestamp::expand(args)
 }
+
+#[proc_macro]
+pub fn throttle_clock(args: TokenStream) -> TokenStream {
+    items::throttle_clock::expand(args)
+}
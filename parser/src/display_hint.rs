@@ -1,5 +1,7 @@
 use std::str::FromStr;
 
+use crate::is_identifier;
+
 /// All display hints
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
@@ -23,6 +25,13 @@ pub enum DisplayHint {
         alternate: bool,
         zero_pad: usize,
     },
+    /// `:.N`, `:e` OR `:E`, formats floats with `N` fractional digits and/or in scientific notation
+    Float {
+        precision: Option<usize>,
+        scientific: bool,
+        uppercase: bool,
+        zero_pad: usize,
+    },
     /// `:a`
     Ascii,
     /// `:?`
@@ -52,6 +61,29 @@ pub enum DisplayHint {
     // Should we allow additional params that give a CDDL that further guides processing (like,
     // when data is not tagged but the shape is known for processing anyway)?
     Cbor,
+    /// `:qN`, `:/N` OR `:scale(F)`, optionally followed by a unit like `mV`, formats integers as
+    /// decimal values that are scaled on the host. A unit on its own, like `:mV`, is printed after
+    /// the unscaled value.
+    Quantity {
+        scale: Option<Scale>,
+        unit: Option<String>,
+    },
+    /// `:mac`, formats bytes as a colon-separated MAC (or EUI-64) address
+    Mac,
+    /// `:uuid`, formats 16 bytes as a UUID
+    Uuid,
+    /// `:ipv4`, formats a `u32` (most significant byte first) or 4 bytes as an IPv4 address
+    Ipv4,
+    /// `:enum(Name)`, formats integers as the name they have in the firmware's
+    /// `defmt::enum_names!` table called `Name`
+    Enum {
+        name: String,
+    },
+    /// `:hexdump` OR `:hexdumpN`, formats bytes as a multi-line offset, hex and ASCII dump with
+    /// `N` (default 16) bytes per line
+    Hexdump {
+        bytes_per_line: usize,
+    },
     /// Display hints currently not supported / understood
     Unknown(String),
 }
@@ -78,6 +110,19 @@ impl DisplayHint {
             0 // default behavior is the same as no zero-padding.
         };
 
+        let precision = if let Some(rest) = s.strip_prefix('.') {
+            let (rest, precision) = parse_integer::<usize>(rest)?;
+            s = rest;
+            Some(precision)
+        } else {
+            None
+        };
+
+        // A precision is only meaningful for floats, reject it in combination with any other hint.
+        if precision.is_some() && !matches!(s, "" | "e" | "E") {
+            return None;
+        }
+
         if let Some(stripped) = s.strip_prefix(BITFLAGS_HINT_START) {
             let parts = stripped.split('@').collect::<Vec<_>>();
             if parts.len() < 3 || parts.len() > 4 {
@@ -92,7 +137,33 @@ impl DisplayHint {
             });
         }
 
+        if let Some(rest) = s.strip_prefix("enum(") {
+            let name = rest.strip_suffix(')')?;
+            if !is_identifier(name) {
+                return None;
+            }
+            return Some(DisplayHint::Enum { name: name.into() });
+        }
+
+        if let Some(rest) = s.strip_prefix("hexdump") {
+            let bytes_per_line = if rest.is_empty() {
+                16
+            } else {
+                match parse_integer::<usize>(rest)? {
+                    ("", n @ 1..=64) => n,
+                    _ => return None,
+                }
+            };
+            return Some(DisplayHint::Hexdump { bytes_per_line });
+        }
+
         Some(match s {
+            "" if precision.is_some() => DisplayHint::Float {
+                precision,
+                scientific: false,
+                uppercase: false,
+                zero_pad,
+            },
             "" => DisplayHint::NoHint { zero_pad },
             "us" => DisplayHint::Seconds(TimePrecision::Micros),
             "ms" => DisplayHint::Seconds(TimePrecision::Millis),
@@ -118,13 +189,114 @@ impl DisplayHint {
                 uppercase: true,
                 zero_pad,
             },
+            "e" => DisplayHint::Float {
+                precision,
+                scientific: true,
+                uppercase: false,
+                zero_pad,
+            },
+            "E" => DisplayHint::Float {
+                precision,
+                scientific: true,
+                uppercase: true,
+                zero_pad,
+            },
             "iso8601ms" => DisplayHint::ISO8601(TimePrecision::Millis),
             "iso8601s" => DisplayHint::ISO8601(TimePrecision::Seconds),
             "cbor" => DisplayHint::Cbor,
+            "mac" => DisplayHint::Mac,
+            "uuid" => DisplayHint::Uuid,
+            "ipv4" => DisplayHint::Ipv4,
             "?" => DisplayHint::Debug,
-            _ => return None,
+            _ => return parse_quantity(s),
+        })
+    }
+}
+
+/// How an integer is scaled before being displayed, see [`DisplayHint::Quantity`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Scale {
+    /// `qN`: fixed-point number with `N` fractional bits, e.g. `q15`
+    FixedPoint(u8),
+    /// `/N`: the value is divided by `N`, e.g. `/100`
+    Divide(u128),
+    /// `scale(F)`: the value is multiplied by `F = mantissa * 10^exponent`, e.g. `scale(0.001)`
+    Decimal { mantissa: i128, exponent: i32 },
+}
+
+/// Units that accept an SI prefix, e.g. `V` -> `mV`.
+const SI_UNITS: &[&str] = &[
+    "A", "Ah", "F", "H", "Hz", "J", "K", "N", "Pa", "T", "V", "W", "Wh", "bar", "g", "lx", "m",
+    "ohm", "Ω",
+];
+const SI_PREFIXES: &[&str] = &["p", "n", "u", "µ", "m", "c", "k", "M", "G"];
+/// Units that are only accepted as-is.
+const PLAIN_UNITS: &[&str] = &["%", "dB", "dBm", "degC", "ppm", "rpm", "°C"];
+
+/// Parses a (scaled) quantity hint like `q15`, `/100 degC`, `scale(0.001)V` or `mV`.
+fn parse_quantity(s: &str) -> Option<DisplayHint> {
+    let (rest, scale) = if let Some(rest) = s.strip_prefix('q') {
+        let (rest, frac_bits) = parse_integer::<u8>(rest)?;
+        if frac_bits >= 128 {
+            return None;
+        }
+        (rest, Some(Scale::FixedPoint(frac_bits)))
+    } else if let Some(rest) = s.strip_prefix('/') {
+        let (rest, divisor) = parse_integer::<u128>(rest)?;
+        if divisor == 0 {
+            return None;
+        }
+        (rest, Some(Scale::Divide(divisor)))
+    } else if let Some(rest) = s.strip_prefix("scale(") {
+        let (factor, rest) = rest.split_once(')')?;
+        (rest, Some(parse_decimal(factor)?))
+    } else {
+        (s, None)
+    };
+
+    // The unit may be separated from the scale by a space.
+    let unit = match rest.strip_prefix(' ').unwrap_or(rest) {
+        "" if scale.is_some() => None,
+        unit if is_unit(unit) => Some(unit.to_string()),
+        _ => return None,
+    };
+
+    Some(DisplayHint::Quantity { scale, unit })
+}
+
+fn is_unit(s: &str) -> bool {
+    PLAIN_UNITS.contains(&s)
+        || SI_UNITS.contains(&s)
+        || SI_PREFIXES.iter().any(|prefix| {
+            s.strip_prefix(prefix)
+                .is_some_and(|s| SI_UNITS.contains(&s))
         })
+}
+
+/// Parses a decimal number like `0.001` or `-2.5` into a [`Scale::Decimal`].
+fn parse_decimal(s: &str) -> Option<Scale> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+
+    let mut mantissa: i128 = 0;
+    for c in int.chars().chain(frac.chars()) {
+        let digit = c.to_digit(10)?;
+        mantissa = mantissa.checked_mul(10)?.checked_add(digit.into())?;
+    }
+    if negative {
+        mantissa = -mantissa;
     }
+
+    Some(Scale::Decimal {
+        mantissa,
+        exponent: -i32::try_from(frac.len()).ok()?,
+    })
 }
 
 /// Precision of timestamp
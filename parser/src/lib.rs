@@ -16,12 +16,13 @@ mod types;
 use std::{borrow::Cow, ops::Range};
 
 pub use crate::{
-    display_hint::{DisplayHint, TimePrecision},
+    display_hint::{DisplayHint, Scale, TimePrecision},
     types::Type,
 };
 
 /// The kinds of error this library can return
 #[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
 pub enum Error {
     #[error("invalid type specifier `{0:?}`")]
     InvalidTypeSpecifier(String),
@@ -47,6 +48,8 @@ pub enum Error {
     ConflictingTypes(usize, Type, Type),
     #[error("argument {0} is not used in this format string")]
     UnusedArgument(usize),
+    #[error("invalid field name `{0:?}`")]
+    InvalidFieldName(String),
 }
 
 /// A format string that a future release will reject.
@@ -73,14 +76,40 @@ pub struct Parameter {
     pub hint: Option<DisplayHint>,
 }
 
+/// A key-value field of the form `{@name=Type:hint}` in a format string.
+///
+/// Fields are not part of the message; printers show them next to it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Field<'f> {
+    /// The name of the field, e.g. `peer`.
+    pub name: &'f str,
+    /// The value of the field.
+    pub parameter: Parameter,
+}
+
 /// A part of a format string.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Fragment<'f> {
     /// A literal string (eg. `"literal "` in `"literal {:?}"`).
     Literal(Cow<'f, str>),
 
     /// A format parameter.
     Parameter(Parameter),
+
+    /// A key-value field.
+    Field(Field<'f>),
+}
+
+impl Fragment<'_> {
+    /// Returns the parameter of a [`Fragment::Parameter`] or [`Fragment::Field`].
+    pub fn parameter(&self) -> Option<&Parameter> {
+        match self {
+            Fragment::Literal(_) => None,
+            Fragment::Parameter(parameter) => Some(parameter),
+            Fragment::Field(field) => Some(&field.parameter),
+        }
+    }
 }
 
 /// A parsed formatting parameter (contents of `{` `}` block).
@@ -88,8 +117,11 @@ pub enum Fragment<'f> {
 /// # Syntax
 ///
 /// ```notrust
-/// param := '{' [ argument ] [ '=' argtype ] [ ':' format_spec ] '}'
+/// param := '{' [ argument | field ] [ '=' argtype ] [ ':' format_spec ] '}'
 /// argument := integer
+/// field := '@' identifier
+///
+/// Fields take the argument indices after those of the other parameters, in order.
 ///
 /// argtype := bitfield | '?' | format-array | '[?]' | byte-array | '[u8]' | 'istr' | 'str' |
 ///     'bool' | 'char' | 'u8' | 'u16' | 'u32' | 'u64' | 'u128' | 'usize' | 'i8' | 'i16' | 'i32' |
@@ -99,9 +131,12 @@ pub enum Fragment<'f> {
 /// byte-array := '[u8;' spaces integer ']'
 /// spaces := ' '*
 ///
-/// format_spec := [ zero_pad ] type
+/// format_spec := [ zero_pad ] [ precision ] type
 /// zero_pad := '0' integer
-/// type := 'a' | 'b' | 'o' | 'x' | 'X' | '?' | 'us'
+/// precision := '.' integer
+/// type := 'a' | 'b' | 'o' | 'x' | 'X' | '?' | 'us' | 'e' | 'E' | quantity
+/// quantity := scale [ ' ' ] [ unit ] | unit
+/// scale := 'q' integer | '/' integer | 'scale(' decimal ')'
 /// ```
 #[derive(Debug, PartialEq)]
 struct Param {
@@ -271,6 +306,16 @@ fn parse_param(mut input: &str, mode: ParserMode) -> Result<Param, Error> {
     Ok(Param { index, ty, hint })
 }
 
+/// Checks whether `s` is a (non-raw) Rust identifier, e.g. `UsbStatus` or `_peer2`.
+pub(crate) fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    s != "_" && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 /// Rejects a brace that is not doubled, matching what `format!` accepts.
 fn check_braces_by_pairing(unescaped_literal: &str) -> Result<(), Warning> {
     let mut chars = unescaped_literal.chars().peekable();
@@ -383,6 +428,9 @@ pub fn parse_with_warnings(
     // Next argument index assigned to a parameter without an explicit one.
     let mut next_arg_index = 0;
 
+    // Number of arguments of the parameters that are not fields.
+    let mut message_args = 0;
+
     let mut chars = format_string.char_indices();
     while let Some((brace_pos, ch)) = chars.next() {
         if ch != '{' {
@@ -411,18 +459,52 @@ pub fn parse_with_warnings(
         end_pos = brace_pos + 1 + len + 1;
 
         // Parse the contents inside the braces.
-        let param_str = &format_string[brace_pos + 1..][..len];
+        let mut param_str = &format_string[brace_pos + 1..][..len];
+        let field_name = match param_str.strip_prefix('@') {
+            Some(field) => {
+                let name_end = field.find(['=', ':']).unwrap_or(field.len());
+                let name = &field[..name_end];
+                if !is_identifier(name) {
+                    return Err(Error::InvalidFieldName(name.to_owned()));
+                }
+                param_str = &field[name_end..];
+                Some(name)
+            }
+            None => None,
+        };
         let param = parse_param(param_str, mode)?;
-        fragments.push(Fragment::Parameter(Parameter {
-            index: param.index.unwrap_or_else(|| {
+        let index = match (field_name, param.index) {
+            // Assigned below, once the arguments of the other parameters are known.
+            (Some(_), _) => usize::MAX,
+            (None, Some(index)) => index,
+            (None, None) => {
                 // If there is no explicit index, assign the next one.
                 let idx = next_arg_index;
                 next_arg_index += 1;
                 idx
-            }),
+            }
+        };
+        if field_name.is_none() {
+            message_args = message_args.max(index + 1);
+        }
+        let parameter = Parameter {
+            index,
             ty: param.ty,
             hint: param.hint,
-        }));
+        };
+        fragments.push(match field_name {
+            Some(name) => Fragment::Field(Field { name, parameter }),
+            None => Fragment::Parameter(parameter),
+        });
+    }
+
+    // Fields take the arguments after those of the other parameters.
+    let mut next_field_index = message_args;
+    for fragment in &mut fragments {
+        if let Fragment::Field(field) = fragment {
+            field.parameter.index = next_field_index;
+            next_field_index += 1;
+        }
     }
 
     // Trailing literal.
@@ -433,7 +515,7 @@ pub fn parse_with_warnings(
     // Check for argument type conflicts.
     let mut args = Vec::new();
     for frag in &fragments {
-        if let Fragment::Parameter(Parameter { index, ty, .. }) = frag {
+        if let Some(Parameter { index, ty, .. }) = frag.parameter() {
             if args.len() <= *index {
                 args.resize(*index + 1, None);
             }
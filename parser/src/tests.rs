@@ -44,7 +44,25 @@ fn all_parse_param_cases(
 #[case(":iso8601ms", DisplayHint::ISO8601(TimePrecision::Millis))]
 #[case(":iso8601s", DisplayHint::ISO8601(TimePrecision::Seconds))]
 #[case(":?", DisplayHint::Debug)]
+#[case(":mac", DisplayHint::Mac)]
+#[case(":uuid", DisplayHint::Uuid)]
+#[case(":ipv4", DisplayHint::Ipv4)]
+#[case(":hexdump", DisplayHint::Hexdump { bytes_per_line: 16 })]
+#[case(":hexdump8", DisplayHint::Hexdump { bytes_per_line: 8 })]
+#[case(":enum(UsbStatus)", DisplayHint::Enum { name: "UsbStatus".into() })]
 #[case(":02", DisplayHint::NoHint { zero_pad: 2 })]
+#[case(":.2", DisplayHint::Float { precision: Some(2), scientific: false, uppercase: false, zero_pad: 0 })]
+#[case(":08.3", DisplayHint::Float { precision: Some(3), scientific: false, uppercase: false, zero_pad: 8 })]
+#[case(":e", DisplayHint::Float { precision: None, scientific: true, uppercase: false, zero_pad: 0 })]
+#[case(":E", DisplayHint::Float { precision: None, scientific: true, uppercase: true, zero_pad: 0 })]
+#[case(":.4e", DisplayHint::Float { precision: Some(4), scientific: true, uppercase: false, zero_pad: 0 })]
+#[case(":q15", DisplayHint::Quantity { scale: Some(Scale::FixedPoint(15)), unit: None })]
+#[case(":/100", DisplayHint::Quantity { scale: Some(Scale::Divide(100)), unit: None })]
+#[case(":/100 degC", DisplayHint::Quantity { scale: Some(Scale::Divide(100)), unit: Some("degC".to_string()) })]
+#[case(":scale(0.001)V", DisplayHint::Quantity { scale: Some(Scale::Decimal { mantissa: 1, exponent: -3 }), unit: Some("V".to_string()) })]
+#[case(":scale(-2.5)", DisplayHint::Quantity { scale: Some(Scale::Decimal { mantissa: -25, exponent: -1 }), unit: None })]
+#[case(":mV", DisplayHint::Quantity { scale: None, unit: Some("mV".to_string()) })]
+#[case(":%", DisplayHint::Quantity { scale: None, unit: Some("%".to_string()) })]
 fn all_display_hints(#[case] input: &str, #[case] hint: DisplayHint) {
     assert_eq!(
         parse_param(input, ParserMode::Strict),
@@ -191,6 +209,17 @@ fn arrays_err(#[case] input: &str) {
 #[case("{dunno=u8:x}", Error::UnexpectedContentInFormatString("dunno=u8:x".to_string()))]
 #[case("{0dunno}", Error::UnexpectedContentInFormatString("dunno".to_string()))]
 #[case("{:}", Error::MalformedFormatString)]
+#[case::precision_without_digits("{=f32:.}", Error::UnknownDisplayHint(".".to_string()))]
+#[case::precision_with_hex("{=u8:.2x}", Error::UnknownDisplayHint(".2x".to_string()))]
+#[case::divide_by_zero("{=u8:/0}", Error::UnknownDisplayHint("/0".to_string()))]
+#[case::fixed_point_too_wide("{=u8:q128}", Error::UnknownDisplayHint("q128".to_string()))]
+#[case::scale_not_a_number("{=u8:scale(x)}", Error::UnknownDisplayHint("scale(x)".to_string()))]
+#[case::unknown_unit("{=u8:/10 furlong}", Error::UnknownDisplayHint("/10 furlong".to_string()))]
+#[case::prefix_on_plain_unit("{=u8:m%}", Error::UnknownDisplayHint("m%".to_string()))]
+#[case::hexdump_zero_width("{=[u8]:hexdump0}", Error::UnknownDisplayHint("hexdump0".to_string()))]
+#[case::hexdump_too_wide("{=[u8]:hexdump65}", Error::UnknownDisplayHint("hexdump65".to_string()))]
+#[case::enum_without_name("{=u8:enum()}", Error::UnknownDisplayHint("enum()".to_string()))]
+#[case::enum_with_path("{=u8:enum(usb::Status)}", Error::UnknownDisplayHint("enum(usb::Status)".to_string()))]
 #[case::stray_braces_1("}string", Error::UnmatchedCloseBracket)]
 #[case::stray_braces_2("{string", Error::UnmatchedOpenBracket)]
 #[case::stray_braces_3("}", Error::UnmatchedCloseBracket)]
@@ -221,10 +250,81 @@ fn arrays_err(#[case] input: &str) {
 #[case::index_0_is_omitted("{1=u8}", Error::UnusedArgument(0))]
 #[case::index_1_is_missing("{2=u8}{=u16}", Error::UnusedArgument(1))]
 #[case::index_0_is_missing("{2=u8}{1=u16}", Error::UnusedArgument(0))]
+#[case::field_without_name("{@}", Error::InvalidFieldName("".to_string()))]
+#[case::field_name_is_not_an_identifier("{@0peer}", Error::InvalidFieldName("0peer".to_string()))]
+#[case::field_with_index("{@peer0=u8}{@peer 0}", Error::InvalidFieldName("peer 0".to_string()))]
 fn error_msg(#[case] input: &str, #[case] err: Error) {
     assert_eq!(parse(input, ParserMode::Strict), Err(err));
 }
 
+#[test]
+fn fields() {
+    assert_eq!(
+        parse("connected {=u8}{@peer}{@rssi=i8:x}", ParserMode::Strict),
+        Ok(vec![
+            Fragment::Literal("connected ".into()),
+            Fragment::Parameter(Parameter {
+                index: 0,
+                ty: Type::U8,
+                hint: None,
+            }),
+            Fragment::Field(Field {
+                name: "peer",
+                parameter: Parameter {
+                    index: 1,
+                    ty: Type::Format,
+                    hint: None,
+                },
+            }),
+            Fragment::Field(Field {
+                name: "rssi",
+                parameter: Parameter {
+                    index: 2,
+                    ty: Type::I8,
+                    hint: Some(DisplayHint::Hexadecimal {
+                        alternate: false,
+                        uppercase: false,
+                        zero_pad: 0,
+                    }),
+                },
+            }),
+        ])
+    );
+}
+
+#[test]
+fn fields_after_explicit_indices() {
+    // `info!("{0=u8} {0=u8}", x, peer = y)`
+    assert_eq!(
+        parse("{0=u8} {0=u8}{@peer}", ParserMode::Strict),
+        Ok(vec![
+            Fragment::Parameter(Parameter {
+                index: 0,
+                ty: Type::U8,
+                hint: None,
+            }),
+            Fragment::Literal(" ".into()),
+            Fragment::Parameter(Parameter {
+                index: 0,
+                ty: Type::U8,
+                hint: None,
+            }),
+            Fragment::Field(Field {
+                name: "peer",
+                parameter: Parameter {
+                    index: 1,
+                    ty: Type::Format,
+                    hint: None,
+                },
+            }),
+        ])
+    );
+
+    // fields come after the highest explicit index
+    let fragments = parse("{1=u8}{0=u16}{@peer}", ParserMode::Strict).unwrap();
+    assert_eq!(fragments[2].parameter().unwrap().index, 2);
+}
+
 #[rstest]
 #[case("}}", "}")]
 #[case("{{", "{")]
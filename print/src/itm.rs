@@ -0,0 +1,205 @@
+//! Demultiplexer for the ITM (Instrumentation Trace Macrocell) protocol.
+//!
+//! A raw SWO capture contains ITM packets: synchronization, overflow, timestamp and extension
+//! packets, and source packets that carry 1, 2 or 4 bytes written to one of the 32 stimulus ports.
+//! [`ItmDemux`] extracts the payload of a single stimulus port, which is what `defmt-itm` writes the
+//! defmt data to, and reports overflow packets, which mean that the ITM dropped packets.
+//!
+//! See appendix D4 "Debug ITM and DWT Packet Protocol" of the ARMv7-M Architecture Reference
+//! Manual.
+
+/// Something found in the ITM packet stream.
+#[derive(Debug, PartialEq)]
+pub enum ItmEvent {
+    /// Payload bytes written to the selected stimulus port.
+    Data(Vec<u8>),
+    /// The ITM's output FIFO overflowed; packets, and with them defmt frames, were lost.
+    Overflow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    /// Expecting a packet header.
+    Header,
+    /// Inside a synchronization packet (a run of zeros terminated by `0x80`).
+    Sync,
+    /// Inside a protocol packet whose payload bytes have the continuation bit (bit 7) set, except
+    /// for the last one.
+    Continuation,
+    /// Inside a source packet with `remaining` payload bytes left.
+    Payload { selected: bool, remaining: u8 },
+}
+
+pub struct ItmDemux {
+    port: u8,
+    state: State,
+}
+
+impl ItmDemux {
+    /// Creates a demultiplexer that extracts the data written to stimulus `port`.
+    pub fn new(port: u8) -> Self {
+        Self {
+            port,
+            state: State::Header,
+        }
+    }
+
+    /// Parses `data`, which may end in the middle of a packet, and returns what it contained.
+    pub fn received(&mut self, data: &[u8]) -> Vec<ItmEvent> {
+        let mut events = vec![];
+        let mut payload = vec![];
+
+        for &byte in data {
+            self.state = match self.state {
+                State::Header => self.header(byte, &mut payload, &mut events),
+                State::Sync => match byte {
+                    0x00 => State::Sync,
+                    // the sync packet ends with a single set bit; anything else means we are out
+                    // of sync, so keep searching for the end of a sync packet
+                    0x80 => State::Header,
+                    _ => State::Sync,
+                },
+                State::Continuation if byte & 0x80 != 0 => State::Continuation,
+                State::Continuation => State::Header,
+                State::Payload {
+                    selected,
+                    remaining,
+                } => {
+                    if selected {
+                        payload.push(byte);
+                    }
+                    match remaining - 1 {
+                        0 => State::Header,
+                        remaining => State::Payload {
+                            selected,
+                            remaining,
+                        },
+                    }
+                }
+            };
+        }
+
+        if !payload.is_empty() {
+            events.push(ItmEvent::Data(payload));
+        }
+        events
+    }
+
+    fn header(&self, header: u8, payload: &mut Vec<u8>, events: &mut Vec<ItmEvent>) -> State {
+        match header & 0b11 {
+            // protocol packets
+            0b00 => match header {
+                0x00 => State::Sync,
+                0x70 => {
+                    if !payload.is_empty() {
+                        events.push(ItmEvent::Data(std::mem::take(payload)));
+                    }
+                    events.push(ItmEvent::Overflow);
+                    State::Header
+                }
+                // local and global timestamps, extension and reserved packets
+                _ if header & 0x80 != 0 => State::Continuation,
+                _ => State::Header,
+            },
+            // source packets; bit 2 distinguishes hardware (DWT) from instrumentation packets
+            size => State::Payload {
+                selected: header & 0b100 == 0 && header >> 3 == self.port,
+                remaining: 1 << (size - 1),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The end of a synchronization packet.
+    const SYNC: [u8; 6] = [0x00, 0x00, 0x00, 0x00, 0x00, 0x80];
+
+    fn instrumentation(port: u8, payload: &[u8]) -> Vec<u8> {
+        let size = match payload.len() {
+            1 => 0b01,
+            2 => 0b10,
+            4 => 0b11,
+            _ => unreachable!(),
+        };
+        let mut packet = vec![port << 3 | size];
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    #[test]
+    fn extracts_selected_port() {
+        let mut stream = SYNC.to_vec();
+        stream.extend(instrumentation(0, &[1, 2, 3, 4]));
+        stream.extend(instrumentation(1, &[0xff, 0xff]));
+        stream.extend(instrumentation(0, &[5]));
+        stream.extend(instrumentation(0, &[6, 7]));
+
+        let mut demux = ItmDemux::new(0);
+        assert_eq!(
+            demux.received(&stream),
+            [ItmEvent::Data(vec![1, 2, 3, 4, 5, 6, 7])]
+        );
+
+        let mut demux = ItmDemux::new(1);
+        assert_eq!(demux.received(&stream), [ItmEvent::Data(vec![0xff, 0xff])]);
+    }
+
+    #[test]
+    fn skips_protocol_and_hardware_packets() {
+        let mut stream = vec![];
+        // local timestamp, single byte
+        stream.push(0x30);
+        // local timestamp with two payload bytes
+        stream.extend([0xc0, 0x81, 0x01]);
+        // global timestamp 1 and 2
+        stream.extend([0x94, 0x80, 0x80, 0x80, 0x00]);
+        stream.extend([0xb4, 0x80, 0x00]);
+        // extension packet
+        stream.extend([0x88, 0x00]);
+        // DWT hardware packet for "port" 0, with a 4-byte payload
+        stream.extend([0b0000_0111, 0x11, 0x22, 0x33, 0x44]);
+        stream.extend(instrumentation(0, &[42]));
+
+        let mut demux = ItmDemux::new(0);
+        assert_eq!(demux.received(&stream), [ItmEvent::Data(vec![42])]);
+    }
+
+    #[test]
+    fn reports_overflow_in_order() {
+        let mut stream = instrumentation(0, &[1]);
+        stream.push(0x70);
+        stream.extend(instrumentation(0, &[2]));
+
+        let mut demux = ItmDemux::new(0);
+        assert_eq!(
+            demux.received(&stream),
+            [
+                ItmEvent::Data(vec![1]),
+                ItmEvent::Overflow,
+                ItmEvent::Data(vec![2])
+            ]
+        );
+    }
+
+    #[test]
+    fn packets_split_across_reads() {
+        let mut stream = SYNC.to_vec();
+        stream.extend([0xc0, 0x81, 0x01]);
+        stream.extend(instrumentation(3, &[1, 2, 3, 4]));
+
+        let mut demux = ItmDemux::new(3);
+        let mut data = vec![];
+        for byte in stream {
+            for event in demux.received(&[byte]) {
+                match event {
+                    ItmEvent::Data(payload) => data.extend(payload),
+                    ItmEvent::Overflow => panic!("unexpected overflow"),
+                }
+            }
+        }
+        assert_eq!(data, [1, 2, 3, 4]);
+    }
+}
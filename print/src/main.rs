@@ -1,15 +1,18 @@
 use std::{
     env,
+    io::BufWriter,
     path::{Path, PathBuf},
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context as _};
 use clap::{Parser, Subcommand};
 use defmt_decoder::{
+    chrome_trace::{ChromeTrace, Tracks},
+    clock_sync::ClockSync,
     log::{
-        format::{Formatter, FormatterConfig, HostFormatter},
-        DefmtLoggerType,
+        format::{Formatter, FormatterConfig, HostFormatter, TimestampMode},
+        DefmtLoggerConfig, DefmtLoggerType,
     },
     DecodeError, Frame, Locations, Table, DEFMT_VERSIONS,
 };
@@ -24,6 +27,10 @@ use tokio::{
 };
 use tokio_serial::{SerialPort, SerialPortBuilderExt, SerialStream};
 
+use crate::itm::{ItmDemux, ItmEvent};
+
+mod itm;
+
 /// Prints defmt-encoded logs to stdout
 #[derive(Parser, Clone)]
 #[command(name = "defmt-print")]
@@ -36,6 +43,10 @@ struct Opts {
     #[arg(long)]
     json: bool,
 
+    /// Add the number of device resets detected before each frame to the JSON output, as `boot`
+    #[arg(long, requires = "json")]
+    boot_counter: bool,
+
     /// A format string for target-generated logs
     #[arg(long)]
     log_format: Option<String>,
@@ -44,6 +55,21 @@ struct Opts {
     #[arg(long)]
     host_log_format: Option<String>,
 
+    /// Print the `{t}` of the log format as the `absolute` timestamp, the time `relative` to the
+    /// first frame, the `delta` to the previous frame or the estimated UTC `wall-clock` time
+    #[arg(long, value_name = "MODE", default_value = "absolute")]
+    timestamps: TimestampMode,
+
+    /// Highlight the timestamps of frames that were logged at least MS milliseconds after the
+    /// previous frame
+    #[arg(long, value_name = "MS")]
+    highlight_gaps: Option<u64>,
+
+    /// The name of the input that the `{source}` log format specifier prints [default: "stdin",
+    /// the TCP address or the serial port]
+    #[arg(long)]
+    source_name: Option<String>,
+
     /// Log any malformed defmt frames that are being skipped
     #[arg(long)]
     show_skipped_frames: bool,
@@ -60,6 +86,32 @@ struct Opts {
     #[arg(short, long)]
     watch_elf: bool,
 
+    /// Also write the frames to FILE as a trace in the Chrome Trace Event format, which Perfetto
+    /// and chrome://tracing can display; requires an integer `defmt::timestamp!`
+    #[arg(long, value_name = "FILE")]
+    chrome_trace: Option<PathBuf>,
+
+    /// Put the log frames of the `--chrome-trace` on a track per log `level` or per `module`
+    #[arg(
+        long,
+        value_name = "TRACKS",
+        default_value = "level",
+        requires = "chrome_trace"
+    )]
+    chrome_trace_tracks: Tracks,
+
+    /// Parse the input as ITM packets, e.g. a raw SWO capture, and decode the data written to
+    /// stimulus port PORT (`--itm=PORT`, 0 by default)
+    #[arg(
+        long,
+        value_name = "PORT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "0",
+        value_parser = clap::value_parser!(u8).range(0..32)
+    )]
+    itm: Option<u8>,
+
     /// Which operation to perform
     #[command(subcommand)]
     command: Option<Command>,
@@ -163,6 +215,8 @@ impl Source {
 
 const READ_BUFFER_SIZE: usize = 1024;
 
+type Trace = ChromeTrace<BufWriter<std::fs::File>>;
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let opts = Opts::parse();
@@ -182,10 +236,23 @@ async fn main() -> anyhow::Result<()> {
         Some(Command::Serial { path, baud, dtr }) => Source::serial(path, baud, dtr)?,
     };
 
+    // Like the source, the trace outlives the reloads of the ELF file
+    let mut trace = match &opts.chrome_trace {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            Some(ChromeTrace::new(
+                BufWriter::new(file),
+                opts.chrome_trace_tracks,
+            )?)
+        }
+        None => None,
+    };
+
     if opts.watch_elf {
-        run_and_watch(opts, &mut source).await
+        run_and_watch(opts, &mut source, &mut trace).await
     } else {
-        run(opts, &mut source).await
+        run(opts, &mut source, &mut trace).await
     }
 }
 
@@ -202,7 +269,11 @@ async fn has_file_changed(rx: &mut Receiver<Result<Event, notify::Error>>, path:
     true
 }
 
-async fn run_and_watch(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
+async fn run_and_watch(
+    opts: Opts,
+    source: &mut Source,
+    trace: &mut Option<Trace>,
+) -> anyhow::Result<()> {
     let (tx, mut rx) = tokio::sync::mpsc::channel(1);
 
     let path = opts.elf.clone().unwrap().canonicalize().unwrap();
@@ -221,20 +292,26 @@ async fn run_and_watch(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
 
     loop {
         select! {
-            r = run(opts.clone(), source) => r?,
+            r = run(opts.clone(), source, trace) => r?,
             _ = has_file_changed(&mut rx, &path) => ()
         }
     }
 }
 
-async fn run(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
+async fn run(opts: Opts, source: &mut Source, trace: &mut Option<Trace>) -> anyhow::Result<()> {
     let Opts {
         elf,
         json,
+        boot_counter,
         log_format,
         host_log_format,
+        timestamps,
+        highlight_gaps,
+        source_name,
         show_skipped_frames,
         verbose,
+        itm,
+        command,
         ..
     } = opts;
 
@@ -254,10 +331,9 @@ async fn run(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
         None
     };
 
-    let logger_type = if json {
-        DefmtLoggerType::Json
-    } else {
-        DefmtLoggerType::Stdout
+    let logger_type = match json {
+        true => DefmtLoggerType::Json,
+        false => DefmtLoggerType::Stdout,
     };
 
     let cloned_format = log_format.clone().unwrap_or_default();
@@ -270,6 +346,8 @@ async fn run(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
     };
 
     formatter_config.is_timestamp_available = table.has_timestamp();
+    formatter_config.timestamps = timestamps;
+    formatter_config.highlight_gaps = highlight_gaps.map(Duration::from_millis);
 
     let cloned_host_format = host_log_format.clone().unwrap_or_default();
     let host_formatter_config = if host_log_format.is_some() {
@@ -280,10 +358,21 @@ async fn run(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
         FormatterConfig::default()
     };
 
-    let formatter = Formatter::new(formatter_config);
-    let host_formatter = HostFormatter::new(host_formatter_config);
-
-    defmt_decoder::log::init_logger(formatter, host_formatter, logger_type, move |metadata| {
+    let source_name = source_name.unwrap_or_else(|| match command {
+        None | Some(Command::Stdin) => "stdin".to_string(),
+        Some(Command::Tcp { host, port, .. }) => format!("{host}:{port}"),
+        Some(Command::Serial { path, .. }) => path.display().to_string(),
+    });
+    let formatter = Formatter::new(formatter_config).with_source_name(&source_name);
+    let host_formatter = HostFormatter::new(host_formatter_config).with_source_name(source_name);
+
+    let logger_config = DefmtLoggerConfig {
+        formatter,
+        host_formatter,
+        logger_type,
+        boot_counter,
+    };
+    defmt_decoder::log::init_logger_with_config(logger_config, move |metadata| {
         match verbose {
             false => defmt_decoder::log::is_defmt_frame(metadata), // We display *all* defmt frames, but nothing else.
             true => true,                                          // We display *all* frames.
@@ -292,24 +381,36 @@ async fn run(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
 
     let mut buf = [0; READ_BUFFER_SIZE];
     let mut stream_decoder = table.new_stream_decoder();
+    let mut clock_sync = ClockSync::default();
+    let mut itm_demux = itm.map(ItmDemux::new);
     let current_dir = env::current_dir()?;
 
-    loop {
-        // read from stdin or tcpstream and push it to the decoder
-        let (n, eof) = source.read(&mut buf).await?;
-
-        // if 0 bytes where read, we reached EOF, so quit
-        if eof {
-            break Ok(());
-        }
-
-        stream_decoder.received(&buf[..n]);
+    // push data to the decoder and decode it
+    let mut decode = |data: &[u8]| -> anyhow::Result<()> {
+        let received = SystemTime::now();
+        stream_decoder.received(data);
 
-        // decode the received data
         loop {
             match stream_decoder.decode() {
-                Ok(frame) => forward_to_logger(&frame, location_info(&locs, &frame, &current_dir)),
-                Err(DecodeError::UnexpectedEof) => break,
+                Ok(mut frame) => {
+                    clock_sync.update(&mut frame, received);
+                    // a marker line would break the JSON output, which has `--boot-counter` instead
+                    if frame.follows_reset() && !json {
+                        println!("(HOST) device reset detected");
+                        println!("└─ {} @ {}:{}", env!("CARGO_PKG_NAME"), file!(), line!());
+                    }
+                    let location_info = location_info(&locs, &frame, &current_dir);
+                    if let Some(trace) = trace.as_mut() {
+                        trace.write_frame(&frame, location_info.2.as_deref())?;
+                    }
+                    forward_to_logger(&frame, location_info);
+                }
+                Err(DecodeError::UnexpectedEof) => {
+                    if let Some(trace) = trace.as_mut() {
+                        trace.flush()?;
+                    }
+                    break Ok(());
+                }
                 Err(DecodeError::Malformed) => match table.encoding().can_recover() {
                     // if recovery is impossible, abort
                     false => return Err(DecodeError::Malformed.into()),
@@ -326,6 +427,38 @@ async fn run(opts: Opts, source: &mut Source) -> anyhow::Result<()> {
                 },
             }
         }
+    };
+
+    loop {
+        // read from stdin or tcpstream and push it to the decoder
+        let (n, eof) = source.read(&mut buf).await?;
+
+        // if 0 bytes where read, we reached EOF, so quit
+        if eof {
+            if let Some(trace) = trace.take() {
+                trace.finish()?;
+            }
+            break Ok(());
+        }
+
+        let Some(itm_demux) = &mut itm_demux else {
+            decode(&buf[..n])?;
+            continue;
+        };
+
+        for event in itm_demux.received(&buf[..n]) {
+            match event {
+                ItmEvent::Data(data) => decode(&data)?,
+                // with `--json`, stdout only carries JSON frames, so the marker goes to stderr
+                ItmEvent::Overflow if json => {
+                    eprintln!("(HOST) ITM overflow, frames may have been lost");
+                }
+                ItmEvent::Overflow => {
+                    println!("(HOST) ITM overflow, frames may have been lost");
+                    println!("└─ {} @ {}:{}", env!("CARGO_PKG_NAME"), file!(), line!());
+                }
+            }
+        }
     }
 }
 
@@ -88,7 +88,17 @@ pub fn test() {
 fn all_backcompat_snapshot_tests() -> Vec<&'static str> {
     all_snapshot_tests()
         .into_iter()
-        .filter(|test| *test != "drop-on-contention")
+        // these use features that older decoders don't know about
+        .filter(|test| {
+            ![
+                "enum-names",
+                "drop-on-contention",
+                "core-id",
+                "rate-limit",
+                "span",
+            ]
+            .contains(test)
+        })
         .collect()
 }
 
@@ -118,14 +118,19 @@ fn test_host(deny_warnings: bool, skip_ui_tests: bool) {
         env.push(("RUSTFLAGS", "--deny warnings"));
     }
 
-    for feat in ["", "unstable-test", "alloc"] {
+    for feat in ["", "unstable-test", "alloc", "logger", "last-frame"] {
         do_test(
             || run_command("cargo", &["check", "--features", feat], None, &env),
             "host",
         );
     }
 
-    for feat in ["unstable-test", "unstable-test,alloc"] {
+    for feat in [
+        "unstable-test",
+        "unstable-test,alloc",
+        "unstable-test,logger",
+        "unstable-test,last-frame",
+    ] {
         do_test(
             || run_command("cargo", &["test", "--features", feat], None, &env),
             "host",
@@ -176,6 +181,20 @@ fn test_cross(deny_warnings: bool) {
                 "cross-03",
             );
         }
+
+        for feature in ["logger", "last-frame"] {
+            do_test(
+                || {
+                    run_command(
+                        "cargo",
+                        &["check", "--target", target, "-p", "defmt", "--features", feature],
+                        None,
+                        &env,
+                    )
+                },
+                "cross",
+            );
+        }
     }
 
     do_test(
@@ -225,7 +244,14 @@ fn test_cross(deny_warnings: bool) {
         );
     }
 
-    for feature in ["print-defmt", "print-rtt"] {
+    for feature in [
+        "print-defmt",
+        "print-rtt",
+        "crash-record,print-defmt",
+        "action-reset,print-defmt",
+        "action-loop",
+        "action-bkpt,hook",
+    ] {
         do_test(
             || {
                 run_command(
@@ -238,6 +264,26 @@ fn test_cross(deny_warnings: bool) {
             "cross",
         );
     }
+
+    for feature in ["print-defmt", "crash-record,print-defmt", "action-bkpt,hook"] {
+        do_test(
+            || {
+                run_command(
+                    "cargo",
+                    &[
+                        "check",
+                        "--target",
+                        "riscv32i-unknown-none-elf",
+                        "--features",
+                        feature,
+                    ],
+                    Some("firmware/panic-probe"),
+                    &env,
+                )
+            },
+            "cross",
+        );
+    }
 }
 
 fn test_lint_cross(deny_warnings: bool) {
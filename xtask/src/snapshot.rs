@@ -18,7 +18,11 @@ pub(crate) fn all_snapshot_tests() -> Vec<&'static str> {
     pub const STABLE_SNAPSHOT_TESTS: &[&str] = &[
         "log",
         "bitflags",
+        "enum-names",
         "timestamp",
+        "core-id",
+        "rate-limit",
+        "span",
         "panic",
         "assert",
         "assert-eq",